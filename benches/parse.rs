@@ -0,0 +1,57 @@
+use arithmetic_parser::{
+    eval_rpn, parse_with_precedence, to_rpn, tokenize, try_parse, try_parse_with, ParserConfig,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+/// The expressions every stage is measured on.
+fn expressions() -> [(&'static str, String); 4] {
+    [
+        ("short", "3a2c4".to_string()),
+        ("long", format!("1{}", "a7b7".repeat(500))),
+        (
+            "nested",
+            format!("{}1{}", "e".repeat(200), "a1f".repeat(200)),
+        ),
+        ("powers", format!("1{}", "c8d4c2d4".repeat(250))),
+    ]
+}
+
+fn bench_stages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, expression) in expressions() {
+        let tokens = tokenize(&expression).unwrap();
+        let queue = to_rpn(&tokens).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("try_parse", name), &expression, |b, e| {
+            b.iter(|| try_parse(black_box(e)))
+        });
+        group.bench_with_input(BenchmarkId::new("tokenize", name), &expression, |b, e| {
+            b.iter(|| tokenize(black_box(e)))
+        });
+        group.bench_with_input(BenchmarkId::new("to_rpn", name), &tokens, |b, t| {
+            b.iter(|| to_rpn(black_box(t)))
+        });
+        group.bench_with_input(BenchmarkId::new("eval_rpn", name), &queue, |b, q| {
+            b.iter(|| eval_rpn(black_box(q)))
+        });
+    }
+    group.finish();
+}
+
+/// The fast path of `parse_with_precedence`, against the Shunting Yard Algorithm.
+fn bench_precedence(c: &mut Criterion) {
+    let mut group = c.benchmark_group("precedence");
+    let config = ParserConfig::default().with_conventional_precedence();
+    let expression = format!("1{}", "a7c3b7d2".repeat(250));
+    group.bench_function("parse_with_precedence", |b| {
+        b.iter(|| parse_with_precedence(black_box(&expression)))
+    });
+    group.bench_function("try_parse_with", |b| {
+        b.iter(|| try_parse_with(black_box(&expression), &config))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_stages, bench_precedence);
+criterion_main!(benches);
@@ -0,0 +1,1167 @@
+use crate::{
+    spanned_rpn, token::Literal, NumberFormat, NumberOrOperator, OpSemantics, Operator, ParseError,
+    ParserConfig, Span,
+};
+use std::{
+    fmt::Write,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// An abstract syntax tree of an expression.
+/// Every node keeps the span of the source it was parsed from,
+/// parentheses excluded, so that evaluation errors can point back to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number {
+        value: i32,
+        span: Span,
+    },
+    /// A named constant with its value, so that it can be told apart from a literal,
+    /// see [`parse_ast_with`].
+    Constant {
+        name: String,
+        value: i32,
+        span: Span,
+    },
+    /// A name whose value is given at evaluation, see [`parse_ast_with_variables`].
+    Variable {
+        name: String,
+        span: Span,
+    },
+    UnaryOp {
+        op: Operator,
+        operand: Box<Expr>,
+        span: Span,
+    },
+    BinOp {
+        op: Operator,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        span: Span,
+    },
+    TernaryOp {
+        op: Operator,
+        operands: Box<[Expr; 3]>,
+        span: Span,
+    },
+}
+
+impl Expr {
+    /// A number leaf, with an empty span.
+    pub fn number(value: i32) -> Self {
+        Expr::Number {
+            value,
+            span: Span::default(),
+        }
+    }
+
+    /// A unary operation, with the span of its operand.
+    /// An operator which is not unary fails at evaluation with [`ParseError::DisallowedOperator`].
+    pub fn unary(op: Operator, operand: Expr) -> Self {
+        Expr::UnaryOp {
+            op,
+            span: operand.span(),
+            operand: Box::new(operand),
+        }
+    }
+
+    /// A binary operation, spanning from its left to its right operand.
+    /// An operator which is not binary fails at evaluation with [`ParseError::DisallowedOperator`].
+    pub fn binop(op: Operator, lhs: Expr, rhs: Expr) -> Self {
+        let span = lhs.span().to(rhs.span());
+        Expr::BinOp {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            span,
+        }
+    }
+
+    /// The span of the source this node was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Number { span, .. }
+            | Expr::Constant { span, .. }
+            | Expr::Variable { span, .. }
+            | Expr::UnaryOp { span, .. }
+            | Expr::BinOp { span, .. }
+            | Expr::TernaryOp { span, .. } => *span,
+        }
+    }
+
+    /// Evaluates the tree, with the same semantics as [`try_parse`](crate::try_parse).
+    pub fn eval(&self) -> Result<i32, ParseError> {
+        self.eval_with(&ParserConfig::default())
+    }
+
+    /// Evaluates the tree, with the same semantics as [`try_parse_with`](crate::try_parse_with).
+    /// A variable is an unknown constant.
+    pub fn eval_with(&self, config: &ParserConfig) -> Result<i32, ParseError> {
+        self.eval_in(config, &|_| None)
+    }
+
+    /// Evaluates the tree, where the value of every variable is looked up by its name.
+    fn eval_in(
+        &self,
+        config: &ParserConfig,
+        lookup: &impl Fn(&str) -> Option<i32>,
+    ) -> Result<i32, ParseError> {
+        match self {
+            Expr::Number { value, .. } | Expr::Constant { value, .. } => Ok(*value),
+            Expr::Variable { name, span } => {
+                lookup(name).ok_or_else(|| ParseError::UnknownConstant(name.clone(), *span))
+            }
+            Expr::UnaryOp { op, operand, span } => {
+                config.apply_unary(*op, operand.eval_in(config, lookup)?, *span)
+            }
+            Expr::BinOp { op, lhs, rhs, span } => {
+                let (n1, n2) = (lhs.eval_in(config, lookup)?, rhs.eval_in(config, lookup)?);
+                config.apply(*op, n1, n2, *span)
+            }
+            Expr::TernaryOp { op, operands, span } => {
+                let [n1, n2, n3] = [0, 1, 2].map(|i| operands[i].eval_in(config, lookup));
+                config.apply_ternary(*op, n1?, n2?, n3?, *span)
+            }
+        }
+    }
+
+    /// Iterates over all the nodes of the tree in pre-order,
+    /// i.e. every node is yielded before its operands, from left to right.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![self] }
+    }
+
+    /// The tree in Reverse Polish Notation (RPN), i.e. its nodes in post-order,
+    /// which can be evaluated with [`eval_rpn`](crate::eval_rpn).
+    /// A variable has no value, so it is an unknown constant.
+    /// The tree is visited without recursion, however deep it is.
+    pub fn to_rpn(&self) -> Result<Vec<NumberOrOperator>, ParseError> {
+        enum Visit<'a> {
+            Expr(&'a Expr),
+            Operator(Operator),
+        }
+        let mut queue = Vec::new();
+        let mut stack = vec![Visit::Expr(self)];
+        while let Some(visit) = stack.pop() {
+            let expr = match visit {
+                Visit::Expr(expr) => expr,
+                Visit::Operator(op) => {
+                    queue.push(NumberOrOperator::Operator(op));
+                    continue;
+                }
+            };
+            // The operator is pushed first, so that it is visited after its operands.
+            match expr {
+                Expr::Number { value, .. } | Expr::Constant { value, .. } => {
+                    queue.push(NumberOrOperator::Number(*value))
+                }
+                Expr::Variable { name, span } => {
+                    return Err(ParseError::UnknownConstant(name.clone(), *span))
+                }
+                Expr::UnaryOp { op, operand, .. } => {
+                    stack.extend([Visit::Operator(*op), Visit::Expr(operand)])
+                }
+                Expr::BinOp { op, lhs, rhs, .. } => {
+                    stack.extend([Visit::Operator(*op), Visit::Expr(rhs), Visit::Expr(lhs)])
+                }
+                Expr::TernaryOp { op, operands, .. } => {
+                    stack.push(Visit::Operator(*op));
+                    stack.extend(operands.iter().rev().map(Visit::Expr));
+                }
+            }
+        }
+        Ok(queue)
+    }
+
+    /// The expression with the rule characters, which evaluates to the same value,
+    /// so `Expr::number(3) + Expr::number(2) * Expr::number(4)` is `3ae2c4f`.
+    /// Operands are parenthesized only where left-to-right application would otherwise
+    /// group them differently, and a negative number is negated, like `n3`,
+    /// so it parses back as a negation: only a tree without negative numbers,
+    /// like the ones of [`parse_ast`], parses back to the same tree.
+    pub fn to_encoded_string(&self) -> String {
+        let mut encoded = String::new();
+        encode(self, &mut encoded);
+        encoded
+    }
+
+    /// The tree in the DOT language of Graphviz, to be rendered with `dot -Tpng`.
+    /// Nodes are numbered in pre-order from `0`, the root, and labelled with their operator,
+    /// or with their value or name for leaves, and every edge goes from an operation to an operand.
+    /// The tree is visited without recursion, however deep it is.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        let mut stack = vec![(self, None)];
+        let mut id = 0usize;
+        while let Some((expr, parent)) = stack.pop() {
+            let label = match expr {
+                Expr::Number { value, .. } => value.to_string(),
+                Expr::Constant { name, .. } | Expr::Variable { name, .. } => name.clone(),
+                Expr::UnaryOp { op, .. } | Expr::BinOp { op, .. } | Expr::TernaryOp { op, .. } => {
+                    op.symbol().to_owned()
+                }
+            };
+            // Writing to a `String` cannot fail.
+            let _ = writeln!(dot, "    {id} [label={label:?}];");
+            if let Some(parent) = parent {
+                let _ = writeln!(dot, "    {parent} -> {id};");
+            }
+            // Pushed in reverse, so that operands are numbered from left to right.
+            match expr {
+                Expr::Number { .. } | Expr::Constant { .. } | Expr::Variable { .. } => {}
+                Expr::UnaryOp { operand, .. } => stack.push((operand, Some(id))),
+                Expr::BinOp { lhs, rhs, .. } => {
+                    stack.extend([(&**rhs, Some(id)), (&**lhs, Some(id))])
+                }
+                Expr::TernaryOp { operands, .. } => {
+                    stack.extend(operands.iter().rev().map(|operand| (operand, Some(id))))
+                }
+            }
+            id += 1;
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The number of nodes on the longest path from the root to a leaf,
+    /// so a single number has depth `1`.
+    /// The tree is visited without recursion, however deep it is.
+    pub fn depth(&self) -> usize {
+        let mut stack = vec![(self, 1)];
+        let mut depth = 0;
+        while let Some((expr, expr_depth)) = stack.pop() {
+            depth = depth.max(expr_depth);
+            match expr {
+                Expr::Number { .. } | Expr::Constant { .. } | Expr::Variable { .. } => {}
+                Expr::UnaryOp { operand, .. } => stack.push((operand, expr_depth + 1)),
+                Expr::BinOp { lhs, rhs, .. } => {
+                    stack.extend([(&**lhs, expr_depth + 1), (&**rhs, expr_depth + 1)])
+                }
+                Expr::TernaryOp { operands, .. } => {
+                    stack.extend(operands.iter().map(|operand| (operand, expr_depth + 1)))
+                }
+            }
+        }
+        depth
+    }
+}
+
+/// Binary operations built with the operators of Rust, so that
+/// `Expr::number(3) + Expr::number(2) * Expr::number(4)` is `3ae2c4f`, with the grouping of Rust.
+macro_rules! expr_operator {
+    ($($trait:ident, $method:ident, $op:expr;)*) => {$(
+        impl $trait for Expr {
+            type Output = Expr;
+
+            fn $method(self, rhs: Expr) -> Expr {
+                Expr::binop($op, self, rhs)
+            }
+        }
+    )*};
+}
+
+expr_operator! {
+    Add, add, Operator::Sum;
+    Sub, sub, Operator::Sub;
+    Mul, mul, Operator::Mul;
+    Div, div, Operator::Div;
+}
+
+impl Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::unary(Operator::Neg, self)
+    }
+}
+
+impl<'a> IntoIterator for &'a Expr {
+    type Item = &'a Expr;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Pre-order iterator over the nodes of an [`Expr`], created by [`Expr::iter`].
+pub struct Iter<'a> {
+    stack: Vec<&'a Expr>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Expr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let expr = self.stack.pop()?;
+        match expr {
+            Expr::Number { .. } | Expr::Constant { .. } | Expr::Variable { .. } => {}
+            Expr::UnaryOp { operand, .. } => self.stack.push(operand),
+            Expr::BinOp { lhs, rhs, .. } => {
+                // The right subtree is pushed first so that the left one is visited first.
+                self.stack.push(rhs);
+                self.stack.push(lhs);
+            }
+            Expr::TernaryOp { operands, .. } => self.stack.extend(operands.iter().rev()),
+        }
+        Some(expr)
+    }
+}
+
+/// Explains in which order the operations of an expression are applied,
+/// since all the operators have the same precedence and are applied from left to right:
+/// the first line is the expression with every operation in parentheses,
+/// followed by the steps of the evaluation, numbered.
+///
+/// ```
+/// assert_eq!(
+///     arithmetic_parser::explain_precedence("3a2c4").unwrap(),
+///     "((3 + 2) * 4)\n1. 3 + 2 = 5\n2. 5 * 4 = 20"
+/// );
+/// ```
+pub fn explain_precedence(string: &str) -> Result<String, ParseError> {
+    let mut explanation = bracketed(&parse_ast(string)?);
+    let (_, steps) = crate::parse_steps(string)?;
+    for (i, step) in steps.iter().enumerate() {
+        explanation.push_str(&format!("\n{}. {step}", i + 1));
+    }
+    Ok(explanation)
+}
+
+/// Appends the rule characters of the tree, see [`Expr::to_encoded_string`].
+fn encode(expr: &Expr, encoded: &mut String) {
+    match expr {
+        // The opposite of `i32::MIN` is not an `i32` literal.
+        Expr::Number {
+            value: i32::MIN, ..
+        } => encoded.push_str("en2147483647b1f"),
+        Expr::Number { value, .. } if *value < 0 => {
+            encoded.push(Operator::Neg.to_char());
+            encoded.push_str(&value.unsigned_abs().to_string());
+        }
+        Expr::Number { value, .. } => encoded.push_str(&value.to_string()),
+        Expr::Constant { name, .. } | Expr::Variable { name, .. } => encoded.push_str(name),
+        Expr::UnaryOp { op, operand, .. } if op.is_postfix() => {
+            let grouped = is_operation(operand) || is_prefixed(operand);
+            encode_operand(operand, grouped, encoded);
+            encoded.push(op.to_char());
+        }
+        Expr::UnaryOp { op, operand, .. } => {
+            encoded.push(op.to_char());
+            let grouped = is_operation(operand) || is_postfixed(operand);
+            encode_operand(operand, grouped, encoded);
+        }
+        // Operators are applied from left to right, so the left operand needs no parentheses.
+        Expr::BinOp { op, lhs, rhs, .. } => {
+            encode_operand(lhs, matches!(**lhs, Expr::TernaryOp { .. }), encoded);
+            encoded.push(op.to_char());
+            encode_operand(rhs, is_operation(rhs), encoded);
+        }
+        Expr::TernaryOp { op, operands, .. } => {
+            for (i, operand) in operands.iter().enumerate() {
+                if i > 0 {
+                    encoded.push(op.to_char());
+                }
+                encode_operand(operand, is_operation(operand), encoded);
+            }
+        }
+    }
+}
+
+/// Appends an operand, in parentheses if `grouped` is set.
+fn encode_operand(operand: &Expr, grouped: bool, encoded: &mut String) {
+    if grouped {
+        encoded.push(Operator::LBra.to_char());
+    }
+    encode(operand, encoded);
+    if grouped {
+        encoded.push(Operator::RBra.to_char());
+    }
+}
+
+/// Whether the tree is an operation with more than one operand.
+fn is_operation(expr: &Expr) -> bool {
+    matches!(expr, Expr::BinOp { .. } | Expr::TernaryOp { .. })
+}
+
+/// Whether the encoded tree starts with a unary operator, a negative number included.
+fn is_prefixed(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number { value, .. } => *value < 0,
+        Expr::UnaryOp { op, .. } => !op.is_postfix(),
+        _ => false,
+    }
+}
+
+/// Whether the encoded tree ends with a unary operator.
+fn is_postfixed(expr: &Expr) -> bool {
+    matches!(expr, Expr::UnaryOp { op, .. } if op.is_postfix())
+}
+
+/// The tree with the conventional symbols of the operators,
+/// where every operation is enclosed in parentheses, like `((3 + 2) * 4)`.
+fn bracketed(expr: &Expr) -> String {
+    match expr {
+        Expr::Number { value, .. } => value.to_string(),
+        Expr::Constant { name, .. } | Expr::Variable { name, .. } => name.clone(),
+        Expr::UnaryOp { op, operand, .. } => format!("{op}({})", bracketed(operand)),
+        Expr::BinOp { op, lhs, rhs, .. } => {
+            format!("({} {op} {})", bracketed(lhs), bracketed(rhs))
+        }
+        Expr::TernaryOp { op, operands, .. } => {
+            let [n1, n2, n3] = [0, 1, 2].map(|i| bracketed(&operands[i]));
+            format!("{op}({n1}, {n2}, {n3})")
+        }
+    }
+}
+
+/// The depth of the abstract syntax tree of an expression, see [`Expr::depth`].
+/// Since operators are applied from left to right, `3a2a2a2` has depth `4` without any parentheses,
+/// while `3ae2ae2a2ff` has depth `4` too, with all the operations nested to the right.
+pub fn ast_depth(string: &str) -> Result<usize, ParseError> {
+    parse_ast(string).map(|expr| expr.depth())
+}
+
+/// An expression in prefix (Polish) notation, i.e. the nodes of its tree in pre-order,
+/// where every operator comes before its operands: `3a2c4` is `* + 3 2 4`.
+/// It can be evaluated with [`eval_prefix`](crate::eval_prefix).
+pub fn to_prefix(string: &str) -> Result<Vec<NumberOrOperator>, ParseError> {
+    let expr = parse_ast(string)?;
+    let prefix = expr.iter().map(|node| match node {
+        Expr::Number { value, .. } | Expr::Constant { value, .. } => {
+            NumberOrOperator::Number(*value)
+        }
+        Expr::Variable { .. } => unreachable!("the tree has no variables"),
+        Expr::UnaryOp { op, .. } | Expr::BinOp { op, .. } | Expr::TernaryOp { op, .. } => {
+            NumberOrOperator::Operator(*op)
+        }
+    });
+    Ok(prefix.collect())
+}
+
+/// The length of the longest chain of operations of an expression where each one
+/// needs the result of the next, i.e. the number of operations on the longest path
+/// of its tree, see [`ast_depth`]: even with every independent operation applied in parallel,
+/// the evaluation takes that many steps. `3a2a2a2` has length `3`, like `3ae2ae2a2ff`,
+/// while `e1a2fce3a4f` has length `2`, and a single number has length `0`.
+pub fn critical_path_length(string: &str) -> Result<usize, ParseError> {
+    ast_depth(string).map(|depth| depth - 1)
+}
+
+/// Parses an expression into its abstract syntax tree.
+/// An empty expression produces the number `0`, as it evaluates to `0`.
+pub fn parse_ast(string: &str) -> Result<Expr, ParseError> {
+    build_ast(string, &ParserConfig::default(), |value: i32, span| {
+        Expr::Number { value, span }
+    })
+}
+
+/// Same as [`parse_ast`], but the grammar is customized by the given [`ParserConfig`],
+/// and every name of one of its [`constants`](ParserConfig::constants) is an [`Expr::Constant`],
+/// so that `2cPI` keeps the name `PI` while its value is a plain number.
+/// Any other name is an [`Expr::Variable`], like with [`parse_ast_with_variables`].
+pub fn parse_ast_with(string: &str, config: &ParserConfig) -> Result<Expr, ParseError> {
+    build_ast(string, config, |leaf: Leaf, span| {
+        leaf.into_expr(string, span)
+    })
+}
+
+/// A leaf of a tree with variables.
+#[derive(Clone, Copy)]
+enum Leaf {
+    Number(i32),
+    /// A named constant, whose name is the source of its span.
+    Constant(i32),
+    /// A variable, whose name is the source of its span.
+    Variable,
+}
+
+impl Leaf {
+    /// The leaf of the tree, with the span it has in `string`.
+    fn into_expr(self, string: &str, span: Span) -> Expr {
+        let name = || string[span.start..span.end].to_owned();
+        match self {
+            Leaf::Number(value) => Expr::Number { value, span },
+            Leaf::Constant(value) => Expr::Constant {
+                name: name(),
+                value,
+                span,
+            },
+            Leaf::Variable => Expr::Variable { name: name(), span },
+        }
+    }
+}
+
+impl Literal for Leaf {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        i32::literal_len(rest, format)
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        i32::parse_literal(literal, format).map(Leaf::Number)
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        i32::from_constant(value).map(Leaf::Constant)
+    }
+
+    fn variable() -> Option<Self> {
+        Some(Leaf::Variable)
+    }
+
+    fn integer_value(self) -> Option<i64> {
+        match self {
+            Leaf::Number(value) => Some(value.into()),
+            _ => None,
+        }
+    }
+}
+
+/// Same as [`parse_ast`], but every name, which would be a constant, is an [`Expr::Variable`]
+/// instead, so that the tree can be evaluated for many values, see [`eval_batch`].
+pub fn parse_ast_with_variables(string: &str) -> Result<Expr, ParseError> {
+    build_ast(string, &ParserConfig::default(), |leaf: Leaf, span| {
+        leaf.into_expr(string, span)
+    })
+}
+
+/// Simplifies an expression with variables, like [`parse_ast_with_variables`],
+/// and encodes it again, see [`Expr::to_encoded_string`], so `Xa0c1` gives `X`.
+/// The tree is simplified from the leaves up, with these rules only:
+///
+/// - an operation whose operands are all numbers is replaced by its value,
+///   failing like [`try_parse`](crate::try_parse), so `1d0aX` is a division by zero;
+/// - `+ 0`, `- 0`, `* 1` and `/ 1` are removed, and so are `0 +` and `1 *`.
+///
+/// Nothing else is rewritten, in particular `* 0` is kept, as the other operand may fail.
+pub fn simplify(string: &str) -> Result<String, ParseError> {
+    simplified(parse_ast_with_variables(string)?).map(|expr| expr.to_encoded_string())
+}
+
+/// The tree simplified with the rules of [`simplify`].
+fn simplified(expr: Expr) -> Result<Expr, ParseError> {
+    let is = |expr: &Expr, n: i32| matches!(*expr, Expr::Number { value, .. } if value == n);
+    let expr = match expr {
+        Expr::UnaryOp { op, operand, span } => Expr::UnaryOp {
+            op,
+            operand: Box::new(simplified(*operand)?),
+            span,
+        },
+        Expr::BinOp { op, lhs, rhs, span } => {
+            let (lhs, rhs) = (simplified(*lhs)?, simplified(*rhs)?);
+            match op {
+                Operator::Sum | Operator::Sub if is(&rhs, 0) => return Ok(lhs),
+                Operator::Mul | Operator::Div if is(&rhs, 1) => return Ok(lhs),
+                Operator::Sum if is(&lhs, 0) => return Ok(rhs),
+                Operator::Mul if is(&lhs, 1) => return Ok(rhs),
+                _ => Expr::BinOp {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+            }
+        }
+        Expr::TernaryOp { op, operands, span } => {
+            let [n1, n2, n3] = *operands;
+            Expr::TernaryOp {
+                op,
+                operands: Box::new([simplified(n1)?, simplified(n2)?, simplified(n3)?]),
+                span,
+            }
+        }
+        leaf => return Ok(leaf),
+    };
+    // The operands are already simplified, so they are numbers if they can be.
+    if expr
+        .iter()
+        .skip(1)
+        .all(|operand| matches!(operand, Expr::Number { .. }))
+    {
+        let span = expr.span();
+        return expr.eval().map(|value| Expr::Number { value, span });
+    }
+    Ok(expr)
+}
+
+/// Evaluates the tree once for every value of the variable named `var`,
+/// so `TcT` gives `9` and `16` for `3` and `4`.
+/// Any other variable is an unknown constant.
+pub fn eval_batch(expr: &Expr, var: &str, values: &[i32]) -> Vec<Result<i32, ParseError>> {
+    let config = ParserConfig::default();
+    values
+        .iter()
+        .map(|&value| expr.eval_in(&config, &|name| (name == var).then_some(value)))
+        .collect()
+}
+
+/// Tells whether the value of an expression with variables, like [`parse_ast_with_variables`],
+/// is negative for some values of them: `XbY` can be, while `XrcYr` cannot.
+/// Without variables, this is whether the value is negative, so an evaluation error is returned.
+/// With them, the possible signs of every operation are tracked from the signs of its operands,
+/// which may be negative, zero or positive, so the answer may be `true` for an expression
+/// that is never negative, but never `false` for one that can be.
+pub fn can_be_negative(string: &str) -> Result<bool, ParseError> {
+    let expr = parse_ast_with_variables(string)?;
+    if !expr
+        .iter()
+        .any(|node| matches!(node, Expr::Variable { .. }))
+    {
+        return expr.eval().map(|n| n < 0);
+    }
+    Ok(signs(&expr) & NEGATIVE != 0)
+}
+
+/// The signs a value may have, as a set of bits.
+const NEGATIVE: u8 = 1;
+const ZERO: u8 = 2;
+const POSITIVE: u8 = 4;
+
+/// The signs the value of a tree may have, where a variable may have any value
+/// and an operation that always fails has no value.
+fn signs(expr: &Expr) -> u8 {
+    // The signs of the results of a binary operation, for every pair of signs of the operands.
+    let pairwise = |a: u8, b: u8, f: fn(u8, u8) -> u8| {
+        let mut signs = 0;
+        for sa in [NEGATIVE, ZERO, POSITIVE]
+            .into_iter()
+            .filter(|s| a & s != 0)
+        {
+            for sb in [NEGATIVE, ZERO, POSITIVE]
+                .into_iter()
+                .filter(|s| b & s != 0)
+            {
+                signs |= f(sa, sb);
+            }
+        }
+        signs
+    };
+    let opposite = |s: u8| (s & ZERO) | (s & NEGATIVE) << 2 | (s & POSITIVE) >> 2;
+    let sum = |a: u8, b: u8| match (a, b) {
+        (ZERO, s) | (s, ZERO) => s,
+        (a, b) if a == b => a,
+        _ => NEGATIVE | ZERO | POSITIVE,
+    };
+    let product = |a: u8, b: u8| match (a, b) {
+        (ZERO, _) | (_, ZERO) => ZERO,
+        (a, b) if a == b => POSITIVE,
+        _ => NEGATIVE,
+    };
+    match expr {
+        Expr::Number { value, .. } | Expr::Constant { value, .. } => match value.signum() {
+            -1 => NEGATIVE,
+            0 => ZERO,
+            _ => POSITIVE,
+        },
+        Expr::Variable { .. } => NEGATIVE | ZERO | POSITIVE,
+        Expr::UnaryOp { op, operand, .. } => {
+            let s = signs(operand);
+            match op {
+                Operator::Neg => opposite(s),
+                Operator::Signum => s,
+                Operator::DigitSum => (s & ZERO) | ((s & !ZERO != 0) as u8 * POSITIVE),
+                // The square root of a negative number fails.
+                Operator::Sqrt => s & (ZERO | POSITIVE),
+                _ => unreachable!("{op:?} is not a unary operator"),
+            }
+        }
+        Expr::BinOp { op, lhs, rhs, .. } => {
+            let (a, b) = (signs(lhs), signs(rhs));
+            match op {
+                Operator::Sum => pairwise(a, b, sum),
+                Operator::Sub => pairwise(a, opposite(b), sum),
+                Operator::Mul => pairwise(a, b, product),
+                // A quotient is rounded toward zero, and a division by zero fails.
+                Operator::Div if b & !ZERO == 0 => 0,
+                Operator::Div | Operator::PercentOf => {
+                    let b = if *op == Operator::Div { b & !ZERO } else { b };
+                    let product = pairwise(a, b, product);
+                    if product == 0 {
+                        0
+                    } else {
+                        product | ZERO
+                    }
+                }
+                Operator::Gcd | Operator::Lcm if a | b == 0 => 0,
+                Operator::Gcd | Operator::Lcm => ZERO | POSITIVE,
+                Operator::RoundTo => 0,
+                _ => unreachable!("{op:?} is not a binary operator"),
+            }
+        }
+        // The clamped value is one of the operands.
+        Expr::TernaryOp { operands, .. } => operands.iter().map(signs).fold(0, |a, b| a | b),
+    }
+}
+
+/// The coefficients `(m, b)` of an expression which is affine in the variable named `var`,
+/// i.e. which is `m * var + b` for every value of `var`, so `3cXa1` gives `(3, 1)`.
+/// An expression that is not affine, like `XcX`, gives `None`, and so does a division
+/// which is not exact for every value, like `Xd2`. Any other variable is an unknown constant.
+pub fn affine_coeffs(string: &str, var: &str) -> Result<Option<(i32, i32)>, ParseError> {
+    affine(
+        &parse_ast_with_variables(string)?,
+        var,
+        &ParserConfig::default(),
+    )
+}
+
+/// The coefficients of [`affine_coeffs`] for a tree.
+/// The operations on constants are evaluated as usual.
+fn affine(expr: &Expr, var: &str, config: &ParserConfig) -> Result<Option<(i32, i32)>, ParseError> {
+    let overflow = |span| move || ParseError::Overflow(span);
+    let coeffs = match expr {
+        Expr::Number { value, .. } | Expr::Constant { value, .. } => (0, *value),
+        Expr::Variable { name, .. } if name == var => (1, 0),
+        Expr::Variable { name, span } => {
+            return Err(ParseError::UnknownConstant(name.clone(), *span))
+        }
+        Expr::UnaryOp { op, operand, span } => match (op, affine(operand, var, config)?) {
+            (_, None) => return Ok(None),
+            (_, Some((0, b))) => (0, config.apply_unary(*op, b, *span)?),
+            (Operator::Neg, Some((m, b))) => (
+                m.checked_neg().ok_or_else(overflow(*span))?,
+                b.checked_neg().ok_or_else(overflow(*span))?,
+            ),
+            _ => return Ok(None),
+        },
+        Expr::BinOp { op, lhs, rhs, span } => {
+            let (Some((m1, b1)), Some((m2, b2))) =
+                (affine(lhs, var, config)?, affine(rhs, var, config)?)
+            else {
+                return Ok(None);
+            };
+            let (m, b) = match op {
+                _ if m1 == 0 && m2 == 0 => (Some(0), Some(config.apply(*op, b1, b2, *span)?)),
+                Operator::Sum => (m1.checked_add(m2), b1.checked_add(b2)),
+                Operator::Sub => (m1.checked_sub(m2), b1.checked_sub(b2)),
+                Operator::Mul if m1 == 0 => (b1.checked_mul(m2), b1.checked_mul(b2)),
+                Operator::Mul if m2 == 0 => (m1.checked_mul(b2), b1.checked_mul(b2)),
+                Operator::Div if m2 == 0 && b2 == 0 => {
+                    return Err(ParseError::DivisionByZero(*span))
+                }
+                // Only an exact division is the same for every value of the variable.
+                Operator::Div
+                    if m2 == 0 && m1.wrapping_rem(b2) == 0 && b1.wrapping_rem(b2) == 0 =>
+                {
+                    (m1.checked_div(b2), b1.checked_div(b2))
+                }
+                _ => return Ok(None),
+            };
+            (
+                m.ok_or_else(overflow(*span))?,
+                b.ok_or_else(overflow(*span))?,
+            )
+        }
+        Expr::TernaryOp { op, operands, span } => {
+            let [n1, n2, n3] = [0, 1, 2].map(|i| affine(&operands[i], var, config));
+            match (n1?, n2?, n3?) {
+                (Some((0, b1)), Some((0, b2)), Some((0, b3))) => {
+                    (0, config.apply_ternary(*op, b1, b2, b3, *span)?)
+                }
+                _ => return Ok(None),
+            }
+        }
+    };
+    Ok(Some(coeffs))
+}
+
+/// Builds the abstract syntax tree of an expression, where `leaf` makes the leaves.
+fn build_ast<N: Literal>(
+    string: &str,
+    config: &ParserConfig,
+    leaf: impl Fn(N, Span) -> Expr,
+) -> Result<Expr, ParseError> {
+    let mut output_stack = Vec::<Expr>::new();
+    for (token, span) in spanned_rpn(string, config, None)? {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push(leaf(n, span)),
+            NumberOrOperator::Operator(op) if op.arity() == 1 => {
+                let operand = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                output_stack.push(Expr::UnaryOp {
+                    op,
+                    span: span.to(operand.span()),
+                    operand: Box::new(operand),
+                });
+            }
+            NumberOrOperator::Operator(op) if op.arity() == 3 => {
+                let third = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let second = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let first = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                output_stack.push(Expr::TernaryOp {
+                    op,
+                    span: first.span().to(third.span()),
+                    operands: Box::new([first, second, third]),
+                });
+            }
+            NumberOrOperator::Operator(op) => {
+                let rhs = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let lhs = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                output_stack.push(Expr::binop(op, lhs, rhs));
+            }
+        }
+    }
+
+    Ok(output_stack.pop().unwrap_or(Expr::number(0)))
+}
+
+#[test]
+fn parse_ast_tests() {
+    let expr = parse_ast("3a2c4").unwrap();
+    let Expr::BinOp { op, lhs, rhs, span } = expr else {
+        panic!("expected a binary operation");
+    };
+    assert_eq!(
+        (op, span, rhs.span()),
+        (Operator::Mul, Span::new(0, 5), Span::new(4, 5))
+    );
+    assert!(matches!(
+        *lhs,
+        Expr::BinOp { op: Operator::Sum, span, .. } if span == Span::new(0, 3)
+    ));
+    assert_eq!(parse_ast("3c4d2aee2a4c41fc4f").unwrap().eval(), Ok(990));
+    let expr = parse_ast("3cn2").unwrap();
+    assert_eq!(expr.eval(), Ok(-6));
+    assert!(matches!(
+        expr.iter().nth(2),
+        Some(Expr::UnaryOp { op: Operator::Neg, span, .. }) if *span == Span::new(2, 4)
+    ));
+    assert_eq!(parse_ast(""), Ok(Expr::number(0)));
+    let expr = parse_ast("15k0k1a2").unwrap();
+    assert_eq!(expr.eval(), Ok(3));
+    assert_eq!(expr.iter().count(), 6);
+    assert_eq!(
+        parse_ast("3a"),
+        Err(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn parse_ast_with_tests() {
+    let config = ParserConfig {
+        constants: [("PI".to_owned(), 3)].into(),
+        ..Default::default()
+    };
+    let expr = parse_ast_with("2cPI", &config).unwrap();
+    let Expr::BinOp { lhs, rhs, .. } = &expr else {
+        panic!("{expr:?} is not a binary operation");
+    };
+    assert_eq!(
+        **lhs,
+        Expr::Number {
+            value: 2,
+            span: Span::new(0, 1)
+        }
+    );
+    assert_eq!(
+        **rhs,
+        Expr::Constant {
+            name: "PI".to_owned(),
+            value: 3,
+            span: Span::new(2, 4)
+        }
+    );
+    assert_eq!(expr.eval(), Ok(6));
+    assert_eq!(bracketed(&expr), "(2 * PI)");
+    assert_eq!(
+        parse_ast_with("2cE", &config).unwrap().eval(),
+        Err(ParseError::UnknownConstant("E".to_owned(), Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn iter_tests() {
+    let expr = parse_ast("3ae2c4f").unwrap();
+    let numbers = expr
+        .iter()
+        .filter_map(|e| match e {
+            Expr::Number { value, .. } => Some(*value),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, [3, 2, 4]);
+    assert_eq!(expr.iter().count(), 5);
+    assert!(matches!(
+        (&expr).into_iter().next(),
+        Some(Expr::BinOp {
+            op: Operator::Sum,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn eval_error_span_tests() {
+    let expr = parse_ast("1ae2a3f").unwrap();
+    assert_eq!(expr.eval(), Ok(6));
+
+    let expr = parse_ast("10a4c3ae5de1b1ff").unwrap();
+    let err = expr.eval().unwrap_err();
+    assert_eq!(err, ParseError::DivisionByZero(Span::new(8, 14)));
+    assert_eq!(err.to_string(), "Division by zero at 8..14");
+}
+
+#[test]
+fn ast_depth_tests() {
+    assert_eq!(ast_depth("3"), Ok(1));
+    assert_eq!(ast_depth(""), Ok(1));
+    assert_eq!(ast_depth("3a2a2a2"), Ok(4));
+    assert_eq!(ast_depth("3ae2ae2a2ff"), Ok(4));
+    assert_eq!(ast_depth("e3a2fcee2a2fa2f"), Ok(4));
+    assert_eq!(ast_depth("nnn3"), Ok(4));
+    assert_eq!(ast_depth("15k0k1a2"), Ok(3));
+    assert_eq!(
+        ast_depth("3ae2"),
+        Err(ParseError::MissingRightParenthesis(Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn critical_path_length_tests() {
+    assert_eq!(critical_path_length("3a2a2a2"), Ok(3));
+    assert_eq!(critical_path_length("e1a2fce3a4f"), Ok(2));
+    assert_eq!(critical_path_length("nn4a1"), Ok(3));
+    assert_eq!(critical_path_length("7"), Ok(0));
+    assert_eq!(critical_path_length(""), Ok(0));
+    assert_eq!(
+        critical_path_length("1a"),
+        Err(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn can_be_negative_tests() {
+    assert_eq!(can_be_negative("3b5"), Ok(true));
+    assert_eq!(can_be_negative("5b3"), Ok(false));
+    assert_eq!(
+        can_be_negative("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(can_be_negative("XbY"), Ok(true));
+    assert_eq!(can_be_negative("XcX"), Ok(true));
+    assert_eq!(can_be_negative("XrcYr"), Ok(false));
+    assert_eq!(can_be_negative("XhYa1"), Ok(false));
+    assert_eq!(can_be_negative("Xk0k10"), Ok(true));
+    assert_eq!(can_be_negative("XqdYqr"), Ok(false));
+    assert_eq!(can_be_negative("nXqbe1f"), Ok(true));
+}
+
+#[test]
+fn eval_batch_tests() {
+    let expr = parse_ast_with_variables("TcTa1").unwrap();
+    assert_eq!(eval_batch(&expr, "T", &[3, 4, 0]), [Ok(10), Ok(17), Ok(1)]);
+    let span = Span::new(0, 1);
+    assert_eq!(
+        eval_batch(&expr, "X", &[3]),
+        [Err(ParseError::UnknownConstant("T".to_owned(), span))]
+    );
+    assert_eq!(
+        eval_batch(&parse_ast_with_variables("1dX").unwrap(), "X", &[1, 0]),
+        [Ok(1), Err(ParseError::DivisionByZero(Span::new(0, 3)))]
+    );
+    assert_eq!(
+        expr.eval(),
+        Err(ParseError::UnknownConstant("T".to_owned(), span))
+    );
+    assert_eq!(
+        parse_ast("T"),
+        Err(ParseError::UnknownConstant("T".to_owned(), span))
+    );
+}
+
+#[test]
+fn affine_coeffs_tests() {
+    assert_eq!(affine_coeffs("3cXa1", "X"), Ok(Some((3, 1))));
+    assert_eq!(affine_coeffs("Xa1c3", "X"), Ok(Some((3, 3))));
+    assert_eq!(affine_coeffs("nXbe2c5f", "X"), Ok(Some((-1, -10))));
+    assert_eq!(affine_coeffs("e4cXa8fd4", "X"), Ok(Some((1, 2))));
+    assert_eq!(affine_coeffs("2k0k5", "X"), Ok(Some((0, 2))));
+    assert_eq!(affine_coeffs("XcX", "X"), Ok(None));
+    assert_eq!(affine_coeffs("Xd2", "X"), Ok(None));
+    assert_eq!(affine_coeffs("sX", "X"), Ok(None));
+    assert_eq!(affine_coeffs("1dX", "X"), Ok(None));
+    assert_eq!(
+        affine_coeffs("Xd0", "X"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        affine_coeffs("XaY", "X"),
+        Err(ParseError::UnknownConstant("Y".to_owned(), Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn to_rpn_tests() {
+    for string in ["3ae2c4f", "3cn2bnn4", "5k0a1k10b2", "12qa16r"] {
+        let expr = parse_ast(string).unwrap();
+        let expected = crate::to_rpn(&crate::tokenize(string).unwrap()).unwrap();
+        assert_eq!(expr.to_rpn(), Ok(expected), "{string}");
+    }
+    let expr = Expr::binop(
+        Operator::Mul,
+        Expr::unary(Operator::Neg, Expr::number(3)),
+        Expr::binop(Operator::Sum, Expr::number(1), Expr::number(2)),
+    );
+    assert_eq!(crate::eval_rpn(&expr.to_rpn().unwrap()), Ok(-9));
+    assert_eq!(
+        parse_ast_with_variables("1aX").unwrap().to_rpn(),
+        Err(ParseError::UnknownConstant("X".to_owned(), Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn to_prefix_tests() {
+    use NumberOrOperator::{Number, Operator as Op};
+    assert_eq!(
+        to_prefix("3a2c4"),
+        Ok(vec![
+            Op(Operator::Mul),
+            Op(Operator::Sum),
+            Number(3),
+            Number(2),
+            Number(4)
+        ])
+    );
+    for string in ["3ae2c4f", "3cn2bnn4", "5k0a1k10b2", "12qa16r", ""] {
+        let prefix = to_prefix(string).unwrap();
+        assert_eq!(
+            crate::eval_prefix(&prefix),
+            crate::try_parse(string),
+            "{string}"
+        );
+    }
+    assert_eq!(
+        crate::eval_prefix(&[Op(Operator::Div), Number(1), Number(0)]),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        crate::eval_prefix(&[Op(Operator::Sum), Number(1)]),
+        Err(ParseError::EmptyOperand(Span::new(0, 1)))
+    );
+    assert_eq!(
+        crate::eval_prefix(&[Number(1), Number(2)]),
+        Err(ParseError::Malformed(Span::new(1, 2)))
+    );
+    assert_eq!(
+        crate::eval_prefix(&[Op(Operator::Sum), Number(1), Op(Operator::RBra), Number(2)]),
+        Err(ParseError::Malformed(Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn expr_operators_tests() {
+    let expr = Expr::number(3) + Expr::number(2) * Expr::number(4);
+    assert_eq!(expr.to_encoded_string(), "3ae2c4f");
+    assert_eq!(expr.eval(), Ok(11));
+    let expr = -(Expr::number(10) - Expr::number(4)) / Expr::number(-3);
+    assert_eq!(expr.to_encoded_string(), "ne10b4fdn3");
+    assert_eq!(expr.eval(), Ok(2));
+    assert_eq!(
+        Expr::number(i32::MIN).to_encoded_string(),
+        "en2147483647b1f"
+    );
+    assert_eq!(
+        Expr::unary(Operator::Sum, Expr::number(1)).eval(),
+        Err(ParseError::DisallowedOperator(
+            Operator::Sum,
+            Span::default()
+        ))
+    );
+    assert_eq!(
+        Expr::binop(Operator::Neg, Expr::number(1), Expr::number(2)).eval(),
+        Err(ParseError::DisallowedOperator(
+            Operator::Neg,
+            Span::default()
+        ))
+    );
+    let expr = Expr::unary(Operator::Neg, Expr::number(-3));
+    assert_eq!(expr.to_encoded_string(), "nn3");
+    for expr in [expr, Expr::number(i32::MIN), -Expr::number(i32::MIN + 1)] {
+        let encoded = expr.to_encoded_string();
+        assert_eq!(
+            parse_ast(&encoded).unwrap().eval(),
+            expr.eval(),
+            "{encoded}"
+        );
+    }
+
+    for string in [
+        "3ae2c4f",
+        "3cn2bnn4",
+        "5k0a1k10b2",
+        "12qa16r",
+        "ne3b1fr",
+        "ne4rf",
+        "e1k0k2fa3",
+    ] {
+        let expr = parse_ast(string).unwrap();
+        let encoded = expr.to_encoded_string();
+        let parsed = parse_ast(&encoded).unwrap();
+        assert_eq!(
+            parsed.to_rpn(),
+            expr.to_rpn(),
+            "{string} is encoded as {encoded}"
+        );
+    }
+}
+
+#[test]
+fn to_dot_tests() {
+    assert_eq!(
+        parse_ast("3ae2c4f").unwrap().to_dot(),
+        "digraph {
+    0 [label=\"+\"];
+    1 [label=\"3\"];
+    0 -> 1;
+    2 [label=\"*\"];
+    0 -> 2;
+    3 [label=\"2\"];
+    2 -> 3;
+    4 [label=\"4\"];
+    2 -> 4;
+}
+"
+    );
+    let dot = parse_ast_with_variables("nXk0k10").unwrap().to_dot();
+    assert!(dot.contains("0 [label=\"clamp\"];"));
+    assert!(dot.contains("1 [label=\"-\"];\n    0 -> 1;\n    2 [label=\"X\"];\n    1 -> 2;"));
+    assert!(dot.contains("0 -> 4;"));
+}
+
+#[test]
+fn simplify_tests() {
+    assert_eq!(simplify("Xa0c1").as_deref(), Ok("X"));
+    assert_eq!(simplify("2c3aX").as_deref(), Ok("6aX"));
+    assert_eq!(simplify("Xae2b2f").as_deref(), Ok("X"));
+    assert_eq!(simplify("0aXc1d1").as_deref(), Ok("X"));
+    assert_eq!(simplify("nXa0cY").as_deref(), Ok("nXcY"));
+    assert_eq!(simplify("Xc0").as_deref(), Ok("Xc0"));
+    assert_eq!(simplify("n3a5").as_deref(), Ok("2"));
+    assert_eq!(
+        simplify("1d0aX"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn explain_precedence_tests() {
+    assert_eq!(
+        explain_precedence("3a2c4"),
+        Ok("((3 + 2) * 4)\n1. 3 + 2 = 5\n2. 5 * 4 = 20".to_owned())
+    );
+    assert_eq!(
+        explain_precedence("3ae2c4f"),
+        Ok("(3 + (2 * 4))\n1. 2 * 4 = 8\n2. 3 + 8 = 11".to_owned())
+    );
+    assert_eq!(
+        explain_precedence("n2a15k0k10"),
+        Ok(
+            "clamp((-(2) + 15), 0, 10)\n1. -(2) = -2\n2. -2 + 15 = 13\n3. clamp(13, 0, 10) = 10"
+                .to_owned()
+        )
+    );
+    assert_eq!(explain_precedence("7"), Ok("7".to_owned()));
+    assert_eq!(
+        explain_precedence("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
@@ -0,0 +1,89 @@
+use crate::{token::is_identifier_char, try_parse_with, ParseError, ParserConfig, Span};
+
+/// Same as [`try_parse_with`], but the expression can be preceded by bindings
+/// of names to the values of other expressions, each ended by a `;`,
+/// like `let T = 3a2; TcTcT`, which is `125`.
+///
+/// A binding is `let NAME = expression`, where `let` is optional and can be surrounded by spaces,
+/// and the name follows the rules of the [`ParserConfig::constants`], which it is added to,
+/// so that further bindings and the final expression can use it.
+/// Every binding is evaluated eagerly, in order, and a name can be bound again.
+pub fn parse_with_bindings(string: &str, config: &ParserConfig) -> Result<i32, ParseError> {
+    let mut config = config.clone();
+    let mut offset = 0;
+    let mut statements = string
+        .split(';')
+        .map(|statement| {
+            let start = offset;
+            offset += statement.len() + 1;
+            trimmed(statement, start)
+        })
+        .collect::<Vec<_>>();
+    // The last statement is the final expression, even if it is empty.
+    let (expression, start) = statements.pop().unwrap_or_default();
+
+    for (binding, binding_start) in statements {
+        let invalid =
+            || ParseError::InvalidBinding(Span::new(binding_start, binding_start + binding.len()));
+        let (name, value) = match binding.strip_prefix("let") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest,
+            _ => binding,
+        }
+        .split_once('=')
+        .ok_or_else(invalid)?;
+        let name = name.trim();
+        if !name.starts_with(|c: char| c.is_ascii_uppercase())
+            || !name.chars().all(is_identifier_char)
+        {
+            return Err(invalid());
+        }
+
+        let value_start = binding_start + binding.len() - value.len();
+        let (value, value_start) = trimmed(value, value_start);
+        let value = try_parse_with(value, &config).map_err(|err| err.shifted(value_start))?;
+        config.constants.insert(name.to_owned(), value.into());
+    }
+
+    try_parse_with(expression, &config).map_err(|err| err.shifted(start))
+}
+
+/// Trims a part of the source starting at `start`, returns it with its new start.
+fn trimmed(string: &str, start: usize) -> (&str, usize) {
+    let trimmed = string.trim_start();
+    (trimmed.trim_end(), start + string.len() - trimmed.len())
+}
+
+#[test]
+fn parse_with_bindings_tests() {
+    let config = ParserConfig::default();
+    assert_eq!(parse_with_bindings("let T = 3a2; TcTcT", &config), Ok(125));
+    assert_eq!(
+        parse_with_bindings("A=2;B=AcA;let A = Ba1; AaB", &config),
+        Ok(9)
+    );
+    assert_eq!(parse_with_bindings("3a2", &config), Ok(5));
+    assert_eq!(parse_with_bindings("let T = 1;", &config), Ok(0));
+    assert_eq!(
+        parse_with_bindings("let T = 3a2; Tc2d0", &config),
+        Err(ParseError::DivisionByZero(Span::new(13, 18)))
+    );
+    assert_eq!(
+        parse_with_bindings("let T = 3ag; T", &config),
+        Err(ParseError::InvalidCharacter('g', Span::new(10, 11)))
+    );
+    assert_eq!(
+        parse_with_bindings("let t = 1; t", &config),
+        Err(ParseError::InvalidBinding(Span::new(0, 9)))
+    );
+    assert_eq!(
+        parse_with_bindings("A=1; B 2; A", &config),
+        Err(ParseError::InvalidBinding(Span::new(5, 8)))
+    );
+    assert_eq!(
+        parse_with_bindings("let T = 1; U", &config),
+        Err(ParseError::UnknownConstant(
+            "U".to_owned(),
+            Span::new(11, 12)
+        ))
+    );
+}
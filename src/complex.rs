@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::{
+    spanned_rpn, token::Literal, Number, NumberFormat, NumberOrOperator, Operator, ParseError,
+    ParserConfig, Span,
+};
+
+/// A complex number, with floating point parts.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn apply(op: Operator, n1: Complex, n2: Complex, span: Span) -> Result<Complex, ParseError> {
+        let result = match op {
+            Operator::Sum => Complex::new(n1.re + n2.re, n1.im + n2.im),
+            Operator::Sub => Complex::new(n1.re - n2.re, n1.im - n2.im),
+            Operator::Mul => n1.mul(n2),
+            Operator::Div if n2 == Complex::default() => {
+                return Err(ParseError::DivisionByZero(span))
+            }
+            Operator::Div => {
+                // Multiplies both terms by the conjugate of the divisor, to get a real divisor.
+                let divisor = n2.re * n2.re + n2.im * n2.im;
+                let dividend = n1.mul(Complex::new(n2.re, -n2.im));
+                Complex::new(dividend.re / divisor, dividend.im / divisor)
+            }
+            Operator::PercentOf => {
+                let product = n1.mul(n2);
+                Complex::new(product.re / 100.0, product.im / 100.0)
+            }
+            Operator::Gcd | Operator::Lcm | Operator::RoundTo => {
+                return Err(ParseError::DisallowedOperator(op, span))
+            }
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        if result.re.is_finite() && result.im.is_finite() {
+            Ok(result)
+        } else {
+            Err(ParseError::Overflow(span))
+        }
+    }
+
+    fn apply_unary(op: Operator, n: Complex) -> Complex {
+        match op {
+            Operator::Neg => Complex::new(-n.re, -n.im),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        }
+    }
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.im < 0.0 {
+            write!(f, "{}-{}j", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}j", self.re, self.im)
+        }
+    }
+}
+
+/// A number, like the ones of [`parse_number`](crate::parse_number),
+/// optionally followed by `j` to make it imaginary.
+impl Literal for Complex {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        let len = Number::literal_len(rest, format);
+        if rest[len..].starts_with('j') {
+            len + 1
+        } else {
+            len
+        }
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        match literal.strip_suffix('j') {
+            Some(im) => Some(Complex::new(
+                0.0,
+                Number::parse_literal(im, format)?.as_f64(),
+            )),
+            None => Some(Complex::new(
+                Number::parse_literal(literal, format)?.as_f64(),
+                0.0,
+            )),
+        }
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        Some(Complex::new(value as f64, 0.0))
+    }
+}
+
+/// Same as [`parse_number`](crate::parse_number), but a number literal followed by `j`
+/// is imaginary, so `3a2j` is `3 + 2j` and `2jc2j` is `-4`.
+/// All the operations are computed on floating point parts,
+/// except for the greatest common divisor, the least common multiple, the clamp,
+/// the signum, the digit sum and the square root,
+/// which are not supported.
+pub fn parse_complex(string: &str) -> Result<Complex, ParseError> {
+    let mut output_stack = Vec::<(Complex, Span)>::new();
+    for (token, span) in spanned_rpn(string, &ParserConfig::default(), None)? {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(o)
+                if matches!(o, Operator::Signum | Operator::DigitSum | Operator::Sqrt) =>
+            {
+                return Err(ParseError::DisallowedOperator(o, span))
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 1 => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                output_stack.push((Complex::apply_unary(o, n), span.to(n_span)));
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 3 => {
+                return Err(ParseError::DisallowedOperator(o, span))
+            }
+            NumberOrOperator::Operator(o) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                output_stack.push((Complex::apply(o, n1, n2, span)?, span));
+            }
+        }
+    }
+
+    Ok(output_stack.pop().map(|(n, _)| n).unwrap_or_default())
+}
+
+#[test]
+fn parse_complex_tests() {
+    assert_eq!(parse_complex("3a2j"), Ok(Complex::new(3.0, 2.0)));
+    assert_eq!(parse_complex("2jc2j"), Ok(Complex::new(-4.0, 0.0)));
+    assert_eq!(parse_complex("e3a2jfce1b1jf"), Ok(Complex::new(5.0, -1.0)));
+    assert_eq!(parse_complex("e1a1jfde1b1jf"), Ok(Complex::new(0.0, 1.0)));
+    assert_eq!(parse_complex("n1.5j"), Ok(Complex::new(0.0, -1.5)));
+    assert_eq!(Complex::new(1.0, -2.5).to_string(), "1-2.5j");
+    assert_eq!(
+        parse_complex("1jd0j"),
+        Err(ParseError::DivisionByZero(Span::new(0, 5)))
+    );
+    assert_eq!(
+        parse_complex("1jj"),
+        Err(ParseError::InvalidCharacter('j', Span::new(2, 3)))
+    );
+}
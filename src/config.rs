@@ -0,0 +1,463 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use crate::{Operator, ParseError, Span};
+
+/// Customizes the grammar accepted by [`try_parse_with`](crate::try_parse_with).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// The operators that may appear in an expression; any other operator
+    /// produces [`ParseError::DisallowedOperator`](crate::ParseError::DisallowedOperator).
+    /// Defaults to [`Operator::ALL`].
+    pub allowed_operators: HashSet<Operator>,
+    /// How the result of a division with a nonzero remainder is rounded.
+    pub round_mode: RoundMode,
+    /// The result of a division by zero, which is an error by default.
+    /// Operators with one of the [`overrides`](ParserConfig::overrides) are not affected.
+    pub div_by_zero: DivByZero,
+    /// The separators used in number literals.
+    pub number_format: NumberFormat,
+    /// Named constants, replaced by their value when the expression is parsed.
+    /// A name starts with an uppercase letter, followed by uppercase letters, digits or `_`,
+    /// like `MAX` or `K_2`, so that it cannot be confused with the operators.
+    /// Any other name produces [`ParseError::UnknownConstant`](crate::ParseError::UnknownConstant).
+    pub constants: HashMap<String, i64>,
+    /// Whether the expression must end as soon as it is complete, i.e. after an operand
+    /// outside of any parentheses, unless a binary operator follows.
+    /// Anything else produces [`ParseError::TrailingInput`](crate::ParseError::TrailingInput),
+    /// e.g. `3a2;extra` or `3a2e4f`. Defaults to `false`.
+    pub strict: bool,
+    /// Binary operators whose built-in semantics are replaced by a custom function,
+    /// see [`ParserConfig::with_override`]. Overrides of other operators are ignored.
+    pub overrides: HashMap<Operator, OperatorOverride>,
+    /// Whether number literals and intermediate results are `i64`, so that only
+    /// the final result must fit in an `i32`, or it produces
+    /// [`ParseError::ResultOverflow`](crate::ParseError::ResultOverflow).
+    /// With it, `1000000c1000000d1000000` is `1000000`. Overrides are not applied.
+    /// Defaults to `false`.
+    pub wide_accumulator: bool,
+    /// The precedence and the associativity of the operators, where a higher precedence
+    /// binds tighter. An operator that is not in the table has precedence `0`
+    /// and is left associative, so by default all of them are applied from left to right.
+    /// See [`ParserConfig::with_conventional_precedence`] for the usual math rules.
+    pub precedence: HashMap<Operator, (u8, Associativity)>,
+    /// The greatest absolute value the result of any operation may have, even if
+    /// the final result is smaller, or it produces
+    /// [`ParseError::IntermediateTooLarge`](crate::ParseError::IntermediateTooLarge).
+    /// Number literals are not operations, so they are not bounded.
+    /// Defaults to `None`, i.e. no bound other than the range of the results.
+    pub max_intermediate: Option<i64>,
+    /// The greatest value a number literal may have, whatever the result, or it produces
+    /// [`ParseError::LiteralTooLarge`](crate::ParseError::LiteralTooLarge),
+    /// to catch stray literals early. Constants are not literals, so they are not bounded,
+    /// and neither are fractional literals. Defaults to `None`.
+    pub max_literal: Option<i32>,
+    /// The greatest absolute value the result of every operation of an operator may have,
+    /// where a larger result is clamped to `-cap..=cap`, so with a cap of `1000` for `a`
+    /// `900a200` is `1000`. A negative cap clamps to `0`.
+    /// Results are capped before they are checked against the
+    /// [`max_intermediate`](ParserConfig::max_intermediate) bound,
+    /// and operations that overflow still fail. The [`wide_accumulator`](ParserConfig::wide_accumulator)
+    /// ignores the caps. Defaults to no caps.
+    pub operator_caps: HashMap<Operator, i32>,
+    /// Whether a pair of parentheses can be empty, like `ef`, standing for `0`,
+    /// so that `3aef` is `3`. Otherwise it produces
+    /// [`ParseError::EmptyParentheses`](crate::ParseError::EmptyParentheses),
+    /// since it is most likely a mistake. Defaults to `false`.
+    pub allow_empty_parentheses: bool,
+    /// Whether the operators can also be written as symbols, `+ - * / ( )`,
+    /// or as the words `plus minus times over`, mixed with the rule characters
+    /// and separated by whitespace, like `3 + 2 times 4`.
+    /// A word must not be followed by other letters, or it produces
+    /// [`ParseError::AmbiguousWord`](crate::ParseError::AmbiguousWord). Defaults to `false`.
+    pub flexible_operators: bool,
+    /// Whether an evaluation error of [`try_parse_with`](crate::try_parse_with) carries
+    /// the operands on the stack when it happened, as
+    /// [`ParseError::WithStack`](crate::ParseError::WithStack), to debug long expressions.
+    /// It costs an allocation per error. Defaults to `false`.
+    pub stack_snapshots: bool,
+}
+
+impl ParserConfig {
+    /// A configuration that forbids division,
+    /// so that evaluation can never fail with a division by zero.
+    pub fn without_division() -> Self {
+        let mut config = Self::default();
+        config.allowed_operators.remove(&Operator::Div);
+        config
+    }
+
+    /// Uses the conventional precedence of math, where multiplication and division
+    /// bind tighter than addition and subtraction, and negation binds tightest,
+    /// so `3a2c4` is `11`. The clamp has the lowest precedence.
+    pub fn with_conventional_precedence(mut self) -> Self {
+        use Associativity::Left;
+        self.precedence = HashMap::from([
+            (Operator::Clamp, (0, Left)),
+            (Operator::Sum, (1, Left)),
+            (Operator::Sub, (1, Left)),
+            (Operator::Mul, (2, Left)),
+            (Operator::Div, (2, Left)),
+            (Operator::PercentOf, (2, Left)),
+            (Operator::Gcd, (2, Left)),
+            (Operator::Lcm, (2, Left)),
+            (Operator::RoundTo, (2, Left)),
+            (Operator::Neg, (3, Left)),
+            (Operator::Signum, (3, Left)),
+        ]);
+        self
+    }
+
+    /// Layers the given configuration on top of this one: every field of `overrides`
+    /// that is not the default replaces the same field of this configuration,
+    /// while the constants, the operator overrides, the precedence table
+    /// and the operator caps are extended.
+    /// A field of `overrides` cannot reset a field of this configuration to the default.
+    ///
+    /// ```
+    /// use arithmetic_parser::{ParserConfig, RoundMode};
+    ///
+    /// let base = ParserConfig::default().with_conventional_precedence();
+    /// let config = base.merge(&ParserConfig {
+    ///     round_mode: RoundMode::Ceil,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(config.round_mode, RoundMode::Ceil);
+    /// assert_eq!(config.precedence, base.precedence);
+    /// ```
+    pub fn merge(&self, overrides: &ParserConfig) -> ParserConfig {
+        let default = ParserConfig::default();
+        let mut merged = self.clone();
+        if overrides.allowed_operators != default.allowed_operators {
+            merged.allowed_operators = overrides.allowed_operators.clone();
+        }
+        if overrides.round_mode != default.round_mode {
+            merged.round_mode = overrides.round_mode;
+        }
+        if overrides.div_by_zero != default.div_by_zero {
+            merged.div_by_zero = overrides.div_by_zero;
+        }
+        if overrides.number_format != default.number_format {
+            merged.number_format = overrides.number_format;
+        }
+        if overrides.max_intermediate != default.max_intermediate {
+            merged.max_intermediate = overrides.max_intermediate;
+        }
+        if overrides.max_literal != default.max_literal {
+            merged.max_literal = overrides.max_literal;
+        }
+        merged.strict |= overrides.strict;
+        merged.wide_accumulator |= overrides.wide_accumulator;
+        merged.allow_empty_parentheses |= overrides.allow_empty_parentheses;
+        merged.flexible_operators |= overrides.flexible_operators;
+        merged.stack_snapshots |= overrides.stack_snapshots;
+        merged.constants.extend(overrides.constants.clone());
+        merged.overrides.extend(overrides.overrides.clone());
+        merged.precedence.extend(overrides.precedence.clone());
+        merged.operator_caps.extend(overrides.operator_caps.clone());
+        merged
+    }
+
+    /// Checks that the result of an operation is within [`ParserConfig::max_intermediate`].
+    pub(crate) fn bounded(&self, n: i32, span: Span) -> Result<i32, ParseError> {
+        match self.max_intermediate {
+            Some(max) if i64::from(n).abs() > max => Err(ParseError::IntermediateTooLarge(span)),
+            _ => Ok(n),
+        }
+    }
+
+    /// Clamps the result of an operation to the [`operator_caps`](ParserConfig::operator_caps).
+    pub(crate) fn capped(&self, op: Operator, n: i32) -> i32 {
+        match self.operator_caps.get(&op) {
+            Some(&cap) => n.clamp(-cap.max(0), cap.max(0)),
+            None => n,
+        }
+    }
+
+    /// Whether the operator `o2`, at the top of the operator stack,
+    /// must be applied before the incoming operator `o1`.
+    pub(crate) fn applies_before(&self, o2: Operator, o1: Operator) -> bool {
+        let precedence = |o| {
+            self.precedence
+                .get(&o)
+                .copied()
+                .unwrap_or((0, Associativity::Left))
+        };
+        let ((p2, _), (p1, a1)) = (precedence(o2), precedence(o1));
+        p2 > p1 || (p2 == p1 && a1 == Associativity::Left)
+    }
+
+    /// Replaces the semantics of a binary operator with the given function,
+    /// which is called with the operands and the span of the operation.
+    ///
+    /// ```
+    /// use arithmetic_parser::{try_parse_with, Operator, ParserConfig};
+    ///
+    /// let config = ParserConfig::default()
+    ///     .with_override(Operator::Div, |n1, n2, _| Ok(n1.checked_div(n2).unwrap_or(0)));
+    /// assert_eq!(try_parse_with("3a4d0", &config), Ok(0));
+    /// ```
+    pub fn with_override(
+        mut self,
+        op: Operator,
+        f: impl Fn(i32, i32, Span) -> Result<i32, ParseError> + Send + Sync + 'static,
+    ) -> Self {
+        self.overrides.insert(op, OperatorOverride(Arc::new(f)));
+        self
+    }
+}
+
+/// A custom function applying a binary operator, see [`ParserConfig::with_override`].
+/// Two overrides are equal only if they share the same function.
+#[derive(Clone)]
+pub struct OperatorOverride(Arc<dyn Fn(i32, i32, Span) -> Result<i32, ParseError> + Send + Sync>);
+
+impl OperatorOverride {
+    pub(crate) fn apply(&self, n1: i32, n2: i32, span: Span) -> Result<i32, ParseError> {
+        (self.0)(n1, n2, span)
+    }
+}
+
+impl fmt::Debug for OperatorOverride {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("OperatorOverride(..)")
+    }
+}
+
+impl PartialEq for OperatorOverride {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for OperatorOverride {}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            allowed_operators: Operator::ALL.into_iter().collect(),
+            round_mode: RoundMode::default(),
+            div_by_zero: DivByZero::default(),
+            number_format: NumberFormat::default(),
+            constants: HashMap::new(),
+            strict: false,
+            overrides: HashMap::new(),
+            wide_accumulator: false,
+            precedence: HashMap::new(),
+            max_intermediate: None,
+            max_literal: None,
+            operator_caps: HashMap::new(),
+            allow_empty_parentheses: false,
+            flexible_operators: false,
+            stack_snapshots: false,
+        }
+    }
+}
+
+/// How a sequence of operators with the same precedence is grouped.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Associativity {
+    /// From left to right, `1b2b3` is `(1 - 2) - 3`.
+    Left,
+    /// From right to left, `1b2b3` is `1 - (2 - 3)`.
+    Right,
+}
+
+/// Rounding applied to a division whose remainder is not zero.
+/// The examples show how `7d2` (3.5) and `0b7d2` (-3.5) are rounded.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum RoundMode {
+    /// Rounds toward zero, like Rust's `/` operator: `3` and `-3`.
+    #[default]
+    Truncate,
+    /// Rounds toward negative infinity: `3` and `-4`.
+    Floor,
+    /// Rounds toward positive infinity: `4` and `-3`.
+    Ceil,
+    /// Rounds to the nearest integer, ties away from zero: `4` and `-4`.
+    HalfUp,
+    /// Rounds to the nearest integer, ties to the even one: `4` and `-4`,
+    /// while `5d2` (2.5) gives `2` and `0b5d2` (-2.5) gives `-2`.
+    HalfEven,
+}
+
+/// The result of a division by zero, see [`ParserConfig::div_by_zero`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DivByZero {
+    /// Produces [`ParseError::DivisionByZero`](crate::ParseError::DivisionByZero).
+    #[default]
+    Error,
+    /// Gives `0`.
+    Zero,
+    /// Gives `i32::MAX`, whatever the sign of the dividend.
+    Max,
+    /// Gives the given value.
+    Custom(i32),
+}
+
+impl DivByZero {
+    /// The result of a division by zero, `None` if it is an error.
+    pub(crate) fn value(self) -> Option<i32> {
+        match self {
+            DivByZero::Error => None,
+            DivByZero::Zero => Some(0),
+            DivByZero::Max => Some(i32::MAX),
+            DivByZero::Custom(n) => Some(n),
+        }
+    }
+}
+
+impl RoundMode {
+    /// Divides `n1` by `n2` according to the rounding mode, returns `None` on overflow.
+    /// The divisor must not be zero.
+    pub(crate) fn divide(self, n1: i64, n2: i64) -> Option<i64> {
+        let quotient = n1.checked_div(n2)?;
+        let remainder = n1 % n2;
+        if remainder == 0 {
+            return Some(quotient);
+        }
+
+        let negative = (n1 < 0) != (n2 < 0);
+        // Ties are found comparing the remainder with what it lacks to reach the divisor,
+        // which unlike doubling the remainder cannot overflow.
+        let remainder = remainder.unsigned_abs();
+        let lacking = n2.unsigned_abs() - remainder;
+        let away_from_zero = match self {
+            RoundMode::Truncate => false,
+            RoundMode::Floor => negative,
+            RoundMode::Ceil => !negative,
+            RoundMode::HalfUp => remainder >= lacking,
+            RoundMode::HalfEven => {
+                remainder > lacking || (remainder == lacking && quotient % 2 != 0)
+            }
+        };
+
+        // With a nonzero remainder the quotient is strictly smaller than `n1`
+        // in magnitude, so moving it one step away from zero cannot overflow.
+        Some(match (away_from_zero, negative) {
+            (false, _) => quotient,
+            (true, false) => quotient + 1,
+            (true, true) => quotient - 1,
+        })
+    }
+}
+
+/// The separators and the base suffixes of number literals.
+/// Neither separator should be a character of the grammar, like an operator.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NumberFormat {
+    /// Separates groups of three digits in the integer part, if any.
+    /// A literal using it must have a first group of up to three digits,
+    /// followed by groups of exactly three digits: with `,` as separator
+    /// `1,000` is a thousand, while `1000,000` and `1,00` are invalid.
+    pub grouping: Option<char>,
+    /// Separates the integer part from the fractional one,
+    /// in the parsers that accept fractional numbers.
+    pub decimal: char,
+    /// Whether an integer literal can end with a letter selecting its base, like in assembly:
+    /// `1010B` is binary, `777O` is octal and `0FFH` is hexadecimal, with uppercase digits.
+    /// A hexadecimal literal must start with a digit, so that it is not a constant,
+    /// and none of them can have grouping separators.
+    pub base_suffixes: bool,
+}
+
+impl NumberFormat {
+    /// No grouping separator and `.` as decimal separator.
+    pub const PLAIN: Self = Self {
+        grouping: None,
+        decimal: '.',
+        base_suffixes: false,
+    };
+
+    /// `,` as grouping separator and `.` as decimal separator, like `1,000.5`.
+    pub const ENGLISH: Self = Self {
+        grouping: Some(','),
+        decimal: '.',
+        base_suffixes: false,
+    };
+
+    /// `.` as grouping separator and `,` as decimal separator, like `1.000,5`.
+    pub const EUROPEAN: Self = Self {
+        grouping: Some('.'),
+        decimal: ',',
+        base_suffixes: false,
+    };
+
+    /// The length in bytes of the integer part at the start of `rest`,
+    /// grouping separators included.
+    pub(crate) fn integer_len(&self, rest: &str) -> usize {
+        if let Some(len) = suffixed_len(rest).filter(|_| self.base_suffixes) {
+            return len;
+        }
+        let mut len = crate::token::digits_len(rest);
+        if let Some(grouping) = self.grouping.filter(|_| len <= 3) {
+            while let Some(group) = rest[len..].strip_prefix(grouping) {
+                // A separator not followed by exactly three digits is not part of the literal.
+                if crate::token::digits_len(group) != 3 {
+                    break;
+                }
+                len += grouping.len_utf8() + 3;
+            }
+        }
+        len
+    }
+
+    /// Removes the grouping separators of a literal, and replaces its decimal separator with `.`.
+    pub(crate) fn normalize<'a>(&self, literal: &'a str) -> Cow<'a, str> {
+        if *self == Self::PLAIN {
+            return Cow::Borrowed(literal);
+        }
+        literal
+            .chars()
+            .filter(|&c| Some(c) != self.grouping)
+            .map(|c| if c == self.decimal { '.' } else { c })
+            .collect()
+    }
+
+    /// The normalized digits of an integer literal and their radix,
+    /// which is selected by the suffix of the literal if [`NumberFormat::base_suffixes`] is set.
+    pub(crate) fn radix<'a>(&self, literal: &'a str) -> (Cow<'a, str>, u32) {
+        let radix = match literal.as_bytes().last() {
+            Some(b'B') if self.base_suffixes => 2,
+            Some(b'O') if self.base_suffixes => 8,
+            Some(b'H') if self.base_suffixes => 16,
+            _ => return (self.normalize(literal), 10),
+        };
+        (Cow::Borrowed(&literal[..literal.len() - 1]), radix)
+    }
+}
+
+/// The length in bytes of the literal with a base suffix at the start of `rest`, if any,
+/// see [`NumberFormat::base_suffixes`].
+fn suffixed_len(rest: &str) -> Option<usize> {
+    let hex_len = rest
+        .find(|c: char| !matches!(c, '0'..='9' | 'A'..='F'))
+        .unwrap_or(rest.len());
+    let (digits, after) = rest.split_at(hex_len);
+    let octal = || digits.bytes().all(|b| matches!(b, b'0'..=b'7'));
+    let len = if after.starts_with('H') || (after.starts_with('O') && octal()) {
+        hex_len + 1
+    } else {
+        // `B` is a hexadecimal digit, so the binary suffix ends the run of digits.
+        let binary = digits.strip_suffix('B')?;
+        if binary.is_empty() || binary.bytes().any(|b| !matches!(b, b'0' | b'1')) {
+            return None;
+        }
+        hex_len
+    };
+    // A suffix followed by the rest of a name is not a suffix, like in `10BIT`.
+    match rest[len..].chars().next() {
+        Some(c) if crate::token::is_identifier_char(c) => None,
+        _ => Some(len),
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::PLAIN
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{NumberOrOperator, Operator, ParseError};
+
+/// The tag of a number, which is followed by its zigzag LEB128 varint.
+/// The tag of an operator is `1` plus its index in [`Operator::ALL`].
+const NUMBER_TAG: u8 = 0;
+
+/// Encodes an expression in Reverse Polish Notation (RPN), as returned by
+/// [`to_rpn`](crate::to_rpn), in a compact binary format, which can be decoded by [`rpn_from_bytes`].
+///
+/// Every item starts with a tag byte, and a number is followed by a varint,
+/// so that small numbers take two bytes only.
+pub fn rpn_to_bytes(queue: &[NumberOrOperator]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(queue.len() * 2);
+    for item in queue {
+        match *item {
+            NumberOrOperator::Number(n) => {
+                bytes.push(NUMBER_TAG);
+                // Zigzag encoding maps small negative numbers to small unsigned ones.
+                let mut n = ((n << 1) ^ (n >> 31)) as u32;
+                while n >= 0x80 {
+                    bytes.push(n as u8 | 0x80);
+                    n >>= 7;
+                }
+                bytes.push(n as u8);
+            }
+            NumberOrOperator::Operator(o) => {
+                let index = Operator::ALL
+                    .iter()
+                    .position(|&op| op == o)
+                    .unwrap_or_default();
+                bytes.push(index as u8 + 1);
+            }
+        }
+    }
+    bytes
+}
+
+/// Decodes an expression in Reverse Polish Notation (RPN) encoded by [`rpn_to_bytes`].
+/// Fails with [`ParseError::InvalidEncoding`] at the first byte that cannot be decoded,
+/// like the tag of a parenthesis.
+pub fn rpn_from_bytes(bytes: &[u8]) -> Result<Vec<NumberOrOperator>, ParseError> {
+    let mut queue = Vec::new();
+    let mut position = 0;
+    while let Some(&tag) = bytes.get(position) {
+        position += 1;
+        if tag != NUMBER_TAG {
+            // Parentheses are never in a queue, so their tags are invalid too.
+            let o = Operator::ALL
+                .get(usize::from(tag) - 1)
+                .filter(|o| o.arity() > 0)
+                .ok_or(ParseError::InvalidEncoding {
+                    position: position - 1,
+                })?;
+            queue.push(NumberOrOperator::Operator(*o));
+            continue;
+        }
+
+        let mut n = 0u32;
+        for shift in (0..).step_by(7) {
+            let byte = *bytes
+                .get(position)
+                .ok_or(ParseError::InvalidEncoding { position })?;
+            // The fifth byte holds the last 4 bits only.
+            if shift == 28 && byte > 0x0f {
+                return Err(ParseError::InvalidEncoding { position });
+            }
+            position += 1;
+            n |= u32::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                break;
+            }
+        }
+        queue.push(NumberOrOperator::Number(
+            (n >> 1) as i32 ^ -((n & 1) as i32),
+        ));
+    }
+    Ok(queue)
+}
+
+#[test]
+fn rpn_bytes_tests() {
+    let queue = crate::to_rpn(&crate::tokenize("3ae2c400fbn1").unwrap()).unwrap();
+    let bytes = rpn_to_bytes(&queue);
+    assert_eq!(bytes, [0, 6, 0, 4, 0, 160, 6, 3, 1, 0, 2, 8, 2]);
+    assert_eq!(rpn_from_bytes(&bytes), Ok(queue));
+
+    let extremes = [i32::MIN, -1, 0, 63, 64, i32::MAX].map(NumberOrOperator::Number);
+    assert_eq!(
+        rpn_from_bytes(&rpn_to_bytes(&extremes)),
+        Ok(extremes.to_vec())
+    );
+    assert_eq!(rpn_from_bytes(&[]), Ok(vec![]));
+    assert_eq!(
+        rpn_from_bytes(&[0, 2, 42]),
+        Err(ParseError::InvalidEncoding { position: 2 })
+    );
+    let parentheses = [Operator::LBra, Operator::RBra].map(NumberOrOperator::Operator);
+    for bytes in [
+        rpn_to_bytes(&parentheses[..1]),
+        rpn_to_bytes(&parentheses[1..]),
+    ] {
+        assert_eq!(
+            rpn_from_bytes(&bytes),
+            Err(ParseError::InvalidEncoding { position: 0 })
+        );
+    }
+    assert_eq!(
+        rpn_from_bytes(&[0, 0x80]),
+        Err(ParseError::InvalidEncoding { position: 2 })
+    );
+    assert_eq!(
+        rpn_from_bytes(&[0, 0xff, 0xff, 0xff, 0xff, 0x1f]),
+        Err(ParseError::InvalidEncoding { position: 5 })
+    );
+}
@@ -0,0 +1,373 @@
+use std::fmt;
+
+use crate::{Operator, Span};
+
+/// The reasons an expression can fail to be parsed or evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A character that is not part of the grammar.
+    InvalidCharacter(char, Span),
+    /// A right parenthesis without a matching left parenthesis, with the span of the former.
+    MissingLeftParenthesis(Span),
+    /// A left parenthesis that is never closed, with its span.
+    MissingRightParenthesis(Span),
+    /// A number literal that does not fit in an `i32`.
+    NumberOverflow(Span),
+    /// An operator without enough operands, e.g. `3a`, with the span of the operator.
+    EmptyOperand(Span),
+    /// A division whose divisor evaluates to zero, with the span of the division.
+    DivisionByZero(Span),
+    /// An operation whose result does not fit in an `i32`, with the span of the operation.
+    Overflow(Span),
+    /// An operator excluded by [`ParserConfig::allowed_operators`](crate::ParserConfig::allowed_operators),
+    /// or applied to another number of operands than its arity, in a tree built by hand.
+    DisallowedOperator(Operator, Span),
+    /// An expression that needs more stack slots than [`parse_fixed`](crate::parse_fixed) provides,
+    /// or with too many operands for [`all_parenthesized_results`](crate::all_parenthesized_results).
+    CapacityExceeded,
+    /// A `$` token evaluated by a [`Repl`](crate::Repl) before any successful evaluation.
+    NoPreviousResult(Span),
+    /// A final result that does not fit in an `i32`, with the span of the expression,
+    /// when [`ParserConfig::wide_accumulator`](crate::ParserConfig::wide_accumulator) is set.
+    ResultOverflow(Span),
+    /// A name that is not one of the [`ParserConfig::constants`](crate::ParserConfig::constants).
+    UnknownConstant(String, Span),
+    /// Input after a complete expression, starting at the given byte `position`,
+    /// when [`ParserConfig::strict`](crate::ParserConfig::strict) is set.
+    TrailingInput { position: usize },
+    /// A byte that cannot be decoded by [`rpn_from_bytes`](crate::rpn_from_bytes),
+    /// at the given `position` of the bytes.
+    InvalidEncoding { position: usize },
+    /// A binding of [`parse_with_bindings`](crate::parse_with_bindings)
+    /// that is not a valid name followed by `=` and an expression.
+    InvalidBinding(Span),
+    /// A clamp whose lower bound is greater than its upper bound, with the span of the clamp.
+    InvalidBounds(Span),
+    /// An operation whose result exceeds
+    /// [`ParserConfig::max_intermediate`](crate::ParserConfig::max_intermediate)
+    /// in absolute value, with the span of the operation.
+    IntermediateTooLarge(Span),
+    /// A pair of parentheses with nothing in between, with the span of both, unless
+    /// [`ParserConfig::allow_empty_parentheses`](crate::ParserConfig::allow_empty_parentheses) is set.
+    EmptyParentheses(Span),
+    /// A word of an operator followed by other letters, like `plusn`, with the span of the whole word,
+    /// when [`ParserConfig::flexible_operators`](crate::ParserConfig::flexible_operators) is set.
+    AmbiguousWord(Span),
+    /// An expression with more operands than its operators combine, like `1e2f`,
+    /// with the span of the first value that no operator combines with the ones before it.
+    Malformed(Span),
+    /// A number literal which does not fit in the bits given to
+    /// [`validate_widths`](crate::validate_widths), with the span of the literal.
+    LiteralTooWide { value: i64, span: Span },
+    /// A number literal greater than the [`max_literal`](crate::ParserConfig::max_literal)
+    /// of the configuration, with the span of the literal.
+    LiteralTooLarge { value: i64, span: Span },
+    /// A binary operator right after another one, like the second `a` of `3aa2`,
+    /// starting at the given byte `position`.
+    ConsecutiveOperators { position: usize },
+    /// A selection of [`parse_span`](crate::parse_span) which is out of the expression
+    /// or cuts one of its tokens, like a number literal, with the span of the selection.
+    InvalidSelection(Span),
+    /// A result of [`parse_roman`](crate::parse_roman) which is not between `1` and `3999`,
+    /// so it has no Roman numeral.
+    NotRoman(i32),
+    /// A division with a nonzero remainder, see [`parse_exact`](crate::parse_exact).
+    InexactDivision { dividend: i32, divisor: i32 },
+    /// A square root of a negative number, with the span of the operation.
+    NegativeSquareRoot(Span),
+    /// A number of decimal places to round to which is not an integer from `0` to `15`,
+    /// see [`Operator::RoundTo`](crate::Operator::RoundTo).
+    InvalidPrecision(Span),
+    /// A random number to draw from an empty range, see [`parse_with_rng`](crate::parse_with_rng).
+    EmptyRange(Span),
+    /// Another error, together with the expression it comes from, see [`ParseError::with_input`].
+    WithInput {
+        error: Box<ParseError>,
+        input: String,
+    },
+    /// An evaluation error, together with the operands on the stack when it happened,
+    /// from the bottom to the top, when
+    /// [`ParserConfig::stack_snapshots`](crate::ParserConfig::stack_snapshots) is set.
+    WithStack {
+        error: Box<ParseError>,
+        stack: Vec<i32>,
+    },
+}
+
+impl ParseError {
+    /// The same error, carrying a copy of the expression it comes from,
+    /// so that it can be reported far from where the expression was parsed.
+    /// An error that already carries an expression is left untouched.
+    pub fn with_input(self, input: &str) -> Self {
+        match self {
+            ParseError::WithInput { .. } => self,
+            error => ParseError::WithInput {
+                error: Box::new(error),
+                input: input.to_owned(),
+            },
+        }
+    }
+
+    /// The expression the error comes from, if it was attached by [`ParseError::with_input`].
+    pub fn input(&self) -> Option<&str> {
+        match self {
+            ParseError::WithInput { input, .. } => Some(input),
+            ParseError::WithStack { error, .. } => error.input(),
+            _ => None,
+        }
+    }
+
+    /// The operands on the stack when the evaluation failed, from the bottom to the top,
+    /// see [`ParserConfig::stack_snapshots`](crate::ParserConfig::stack_snapshots).
+    pub fn stack_snapshot(&self) -> Option<&[i32]> {
+        match self {
+            ParseError::WithStack { stack, .. } => Some(stack),
+            ParseError::WithInput { error, .. } => error.stack_snapshot(),
+            _ => None,
+        }
+    }
+
+    /// The span of the source the error refers to,
+    /// if it can be pinned to a specific part of the expression.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::InvalidCharacter(_, span)
+            | ParseError::NumberOverflow(span)
+            | ParseError::EmptyOperand(span)
+            | ParseError::DivisionByZero(span)
+            | ParseError::Overflow(span)
+            | ParseError::DisallowedOperator(_, span)
+            | ParseError::NoPreviousResult(span)
+            | ParseError::ResultOverflow(span)
+            | ParseError::InvalidBinding(span)
+            | ParseError::InvalidBounds(span)
+            | ParseError::IntermediateTooLarge(span)
+            | ParseError::EmptyParentheses(span)
+            | ParseError::AmbiguousWord(span)
+            | ParseError::NegativeSquareRoot(span)
+            | ParseError::InvalidPrecision(span)
+            | ParseError::EmptyRange(span)
+            | ParseError::Malformed(span)
+            | ParseError::InvalidSelection(span)
+            | ParseError::UnknownConstant(_, span)
+            | ParseError::MissingLeftParenthesis(span)
+            | ParseError::MissingRightParenthesis(span) => Some(*span),
+            ParseError::TrailingInput { position } => Some(Span::new(*position, *position)),
+            ParseError::ConsecutiveOperators { position } => {
+                Some(Span::new(*position, *position + 1))
+            }
+            ParseError::LiteralTooWide { span, .. } | ParseError::LiteralTooLarge { span, .. } => {
+                Some(*span)
+            }
+            ParseError::CapacityExceeded
+            | ParseError::InvalidEncoding { .. }
+            | ParseError::NotRoman(_)
+            | ParseError::InexactDivision { .. } => None,
+            ParseError::WithInput { error, .. } | ParseError::WithStack { error, .. } => {
+                error.span()
+            }
+        }
+    }
+
+    /// The same error, for an expression that starts at `offset` of a larger source.
+    pub(crate) fn shifted(self, offset: usize) -> Self {
+        let shift = |span: Span| Span::new(span.start + offset, span.end + offset);
+        match self {
+            ParseError::InvalidCharacter(c, span) => ParseError::InvalidCharacter(c, shift(span)),
+            ParseError::NumberOverflow(span) => ParseError::NumberOverflow(shift(span)),
+            ParseError::EmptyOperand(span) => ParseError::EmptyOperand(shift(span)),
+            ParseError::DivisionByZero(span) => ParseError::DivisionByZero(shift(span)),
+            ParseError::Overflow(span) => ParseError::Overflow(shift(span)),
+            ParseError::DisallowedOperator(o, span) => {
+                ParseError::DisallowedOperator(o, shift(span))
+            }
+            ParseError::NoPreviousResult(span) => ParseError::NoPreviousResult(shift(span)),
+            ParseError::ResultOverflow(span) => ParseError::ResultOverflow(shift(span)),
+            ParseError::UnknownConstant(name, span) => {
+                ParseError::UnknownConstant(name, shift(span))
+            }
+            ParseError::InvalidBinding(span) => ParseError::InvalidBinding(shift(span)),
+            ParseError::InvalidBounds(span) => ParseError::InvalidBounds(shift(span)),
+            ParseError::IntermediateTooLarge(span) => ParseError::IntermediateTooLarge(shift(span)),
+            ParseError::EmptyParentheses(span) => ParseError::EmptyParentheses(shift(span)),
+            ParseError::AmbiguousWord(span) => ParseError::AmbiguousWord(shift(span)),
+            ParseError::NegativeSquareRoot(span) => ParseError::NegativeSquareRoot(shift(span)),
+            ParseError::InvalidPrecision(span) => ParseError::InvalidPrecision(shift(span)),
+            ParseError::EmptyRange(span) => ParseError::EmptyRange(shift(span)),
+            ParseError::Malformed(span) => ParseError::Malformed(shift(span)),
+            ParseError::InvalidSelection(span) => ParseError::InvalidSelection(shift(span)),
+            ParseError::TrailingInput { position } => ParseError::TrailingInput {
+                position: position + offset,
+            },
+            ParseError::ConsecutiveOperators { position } => ParseError::ConsecutiveOperators {
+                position: position + offset,
+            },
+            ParseError::LiteralTooWide { value, span } => ParseError::LiteralTooWide {
+                value,
+                span: shift(span),
+            },
+            ParseError::LiteralTooLarge { value, span } => ParseError::LiteralTooLarge {
+                value,
+                span: shift(span),
+            },
+            ParseError::MissingLeftParenthesis(span) => {
+                ParseError::MissingLeftParenthesis(shift(span))
+            }
+            ParseError::MissingRightParenthesis(span) => {
+                ParseError::MissingRightParenthesis(shift(span))
+            }
+            ParseError::CapacityExceeded
+            | ParseError::InvalidEncoding { .. }
+            | ParseError::NotRoman(_)
+            | ParseError::InexactDivision { .. } => self,
+            ParseError::WithInput { error, input } => ParseError::WithInput {
+                error: Box::new(error.shifted(offset)),
+                input,
+            },
+            ParseError::WithStack { error, stack } => ParseError::WithStack {
+                error: Box::new(error.shifted(offset)),
+                stack,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidCharacter(c, span) => write!(f, "Invalid character '{c}' at {span}"),
+            ParseError::MissingLeftParenthesis(span) => {
+                write!(f, "Invalid expression, missing left parenthesis for {span}")
+            }
+            ParseError::MissingRightParenthesis(span) => {
+                write!(
+                    f,
+                    "Invalid expression, missing right parenthesis for {span}"
+                )
+            }
+            ParseError::NumberOverflow(span) => write!(f, "Number literal out of range at {span}"),
+            ParseError::EmptyOperand(span) => {
+                write!(f, "Invalid expression, missing operand at {span}")
+            }
+            ParseError::DivisionByZero(span) => write!(f, "Division by zero at {span}"),
+            ParseError::Overflow(span) => write!(f, "Arithmetic overflow at {span}"),
+            ParseError::DisallowedOperator(o, span) => {
+                write!(f, "Operator {o:?} is not allowed at {span}")
+            }
+            ParseError::CapacityExceeded => write!(f, "Expression exceeds the stack capacity"),
+            ParseError::NoPreviousResult(span) => {
+                write!(f, "There is no previous result at {span}")
+            }
+            ParseError::ResultOverflow(span) => write!(f, "Result out of range at {span}"),
+            ParseError::UnknownConstant(name, span) => {
+                write!(f, "Unknown constant '{name}' at {span}")
+            }
+            ParseError::TrailingInput { position } => {
+                write!(f, "Unexpected input after the expression at {position}")
+            }
+            ParseError::InvalidEncoding { position } => {
+                write!(f, "Invalid encoded expression at byte {position}")
+            }
+            ParseError::InvalidBinding(span) => write!(f, "Invalid binding at {span}"),
+            ParseError::InvalidBounds(span) => {
+                write!(f, "Lower bound greater than the upper bound at {span}")
+            }
+            ParseError::IntermediateTooLarge(span) => {
+                write!(f, "Intermediate result too large at {span}")
+            }
+            ParseError::EmptyParentheses(span) => {
+                write!(f, "Invalid expression, empty parentheses at {span}")
+            }
+            ParseError::AmbiguousWord(span) => write!(f, "Ambiguous operator word at {span}"),
+            ParseError::Malformed(span) => {
+                write!(
+                    f,
+                    "Invalid expression, operand without an operator at {span}"
+                )
+            }
+            ParseError::ConsecutiveOperators { position } => {
+                write!(f, "Invalid expression, consecutive operators at {position}")
+            }
+            ParseError::LiteralTooWide { value, span } => {
+                write!(f, "Number literal {value} too wide at {span}")
+            }
+            ParseError::LiteralTooLarge { value, span } => {
+                write!(f, "Number literal {value} too large at {span}")
+            }
+            ParseError::InvalidSelection(span) => {
+                write!(f, "Selection {span} does not cover whole tokens")
+            }
+            ParseError::NotRoman(n) => write!(f, "{n} has no Roman numeral"),
+            ParseError::InexactDivision { dividend, divisor } => {
+                write!(f, "{dividend} is not divisible by {divisor}")
+            }
+            ParseError::NegativeSquareRoot(span) => {
+                write!(f, "Square root of a negative number at {span}")
+            }
+            ParseError::InvalidPrecision(span) => {
+                write!(f, "Invalid number of decimal places at {span}")
+            }
+            ParseError::EmptyRange(span) => write!(f, "Empty range of random numbers at {span}"),
+            ParseError::WithInput { error, input } => write!(f, "{error} in '{input}'"),
+            ParseError::WithStack { error, stack } => write!(f, "{error}, with stack {stack:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Renders an error of the given expression like a compiler diagnostic:
+/// the message is followed by the source, with a `^` caret under every character
+/// of the offending span, if the error has one.
+///
+/// ```
+/// let err = arithmetic_parser::try_parse("1ae3d0f").unwrap_err();
+/// assert_eq!(
+///     arithmetic_parser::render_error("1ae3d0f", &err),
+///     "error: Division by zero at 3..6\n1ae3d0f\n   ^^^"
+/// );
+/// ```
+pub fn render_error(string: &str, err: &ParseError) -> String {
+    let mut rendered = format!("error: {err}\n{string}");
+    if let Some(span) = err.span() {
+        // Spans are byte ranges, while the caret is aligned by characters.
+        let column = string[..span.start].chars().count();
+        let width = string[span.start..span.end].chars().count().max(1);
+        rendered.push('\n');
+        rendered.extend(std::iter::repeat_n(' ', column));
+        rendered.extend(std::iter::repeat_n('^', width));
+    }
+    rendered
+}
+
+#[test]
+fn render_error_tests() {
+    let render = |string| render_error(string, &crate::try_parse(string).unwrap_err());
+    assert_eq!(
+        render("3a99999999999"),
+        "error: Number literal out of range at 2..13\n3a99999999999\n  ^^^^^^^^^^^"
+    );
+    assert_eq!(
+        render("3a2g"),
+        "error: Invalid character 'g' at 3..4\n3a2g\n   ^"
+    );
+    assert_eq!(render("3é"), "error: Invalid character 'é' at 1..3\n3é\n ^");
+    assert_eq!(
+        render("3ae2"),
+        "error: Invalid expression, missing right parenthesis for 2..3\n3ae2\n  ^"
+    );
+}
+
+#[test]
+fn with_input_tests() {
+    let err = crate::try_parse_ctx("3a2g").unwrap_err();
+    assert_eq!(err.input(), Some("3a2g"));
+    assert_eq!(err.span(), Some(Span::new(3, 4)));
+    assert_eq!(err.clone().with_input("other"), err);
+    assert_eq!(
+        render_error("3a2g", &err),
+        "error: Invalid character 'g' at 3..4 in '3a2g'\n3a2g\n   ^"
+    );
+    assert_eq!(crate::try_parse("3a2g").unwrap_err().input(), None);
+    assert_eq!(crate::try_parse_ctx("3a2"), Ok(5));
+}
@@ -0,0 +1,245 @@
+use crate::{
+    spanned_rpn, token::Literal, NumberFormat, NumberOrOperator, Operator, ParseError,
+    ParserConfig, Rational, Span,
+};
+
+/// The arithmetic of a type of numbers that expressions can be evaluated over,
+/// see [`parse_over`].
+///
+/// The `span` of the operation is the one to report in case of error,
+/// like [`ParseError::DivisionByZero`] or [`ParseError::Overflow`].
+pub trait Field: Copy {
+    /// The sum of the numbers.
+    fn add(self, other: Self, span: Span) -> Result<Self, ParseError>;
+
+    /// The difference of the numbers.
+    fn sub(self, other: Self, span: Span) -> Result<Self, ParseError>;
+
+    /// The product of the numbers.
+    fn mul(self, other: Self, span: Span) -> Result<Self, ParseError>;
+
+    /// The quotient of the numbers, which fails if `other` is zero.
+    fn div(self, other: Self, span: Span) -> Result<Self, ParseError>;
+
+    /// Parses the digits of a non negative number literal in the given radix,
+    /// returns `None` if it is out of range.
+    fn from_str_radix(digits: &str, radix: u32) -> Option<Self>;
+
+    /// The opposite of the number, which defaults to subtracting it from zero.
+    fn neg(self, span: Span) -> Result<Self, ParseError> {
+        let zero = Self::from_str_radix("0", 10).ok_or(ParseError::Overflow(span))?;
+        zero.sub(self, span)
+    }
+}
+
+macro_rules! checked_field {
+    ($($t:ty),*) => {$(
+        /// Checked arithmetic, with divisions rounded toward zero.
+        impl Field for $t {
+            fn add(self, other: Self, span: Span) -> Result<Self, ParseError> {
+                self.checked_add(other).ok_or(ParseError::Overflow(span))
+            }
+
+            fn sub(self, other: Self, span: Span) -> Result<Self, ParseError> {
+                self.checked_sub(other).ok_or(ParseError::Overflow(span))
+            }
+
+            fn mul(self, other: Self, span: Span) -> Result<Self, ParseError> {
+                self.checked_mul(other).ok_or(ParseError::Overflow(span))
+            }
+
+            fn div(self, other: Self, span: Span) -> Result<Self, ParseError> {
+                if other == 0 {
+                    return Err(ParseError::DivisionByZero(span));
+                }
+                self.checked_div(other).ok_or(ParseError::Overflow(span))
+            }
+
+            fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+                <$t>::from_str_radix(digits, radix).ok()
+            }
+        }
+    )*};
+}
+
+checked_field!(i32, i64);
+
+/// Floating point arithmetic, where a result which is no longer finite overflows.
+impl Field for f64 {
+    fn add(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        finite(self + other, span)
+    }
+
+    fn sub(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        finite(self - other, span)
+    }
+
+    fn mul(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        finite(self * other, span)
+    }
+
+    fn div(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        if other == 0.0 {
+            return Err(ParseError::DivisionByZero(span));
+        }
+        finite(self / other, span)
+    }
+
+    fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        u64::from_str_radix(digits, radix).ok().map(|n| n as f64)
+    }
+}
+
+fn finite(n: f64, span: Span) -> Result<f64, ParseError> {
+    if n.is_finite() {
+        Ok(n)
+    } else {
+        Err(ParseError::Overflow(span))
+    }
+}
+
+/// Exact arithmetic, see [`parse_rational`](crate::parse_rational).
+impl Field for Rational {
+    fn add(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        Rational::apply(Operator::Sum, self, other, span)
+    }
+
+    fn sub(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        Rational::apply(Operator::Sub, self, other, span)
+    }
+
+    fn mul(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        Rational::apply(Operator::Mul, self, other, span)
+    }
+
+    fn div(self, other: Self, span: Span) -> Result<Self, ParseError> {
+        Rational::apply(Operator::Div, self, other, span)
+    }
+
+    fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        Rational::new(i64::from_str_radix(digits, radix).ok()?, 1)
+    }
+}
+
+/// A number of a [`Field`], as a literal of the lexer.
+#[derive(Clone, Copy)]
+struct Over<F>(F);
+
+impl<F: Field> Literal for Over<F> {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        format.integer_len(rest)
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (digits, radix) = format.radix(literal);
+        F::from_str_radix(&digits, radix).map(Over)
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        let n = F::from_str_radix(&value.unsigned_abs().to_string(), 10)?;
+        if value < 0 {
+            n.neg(Span::default()).ok().map(Over)
+        } else {
+            Some(Over(n))
+        }
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but the expression is evaluated over any [`Field`],
+/// so `parse_over::<f64>("7d2")` is `3.5`.
+/// Only the arithmetic operators and the negation are supported,
+/// any other operator produces [`ParseError::DisallowedOperator`].
+pub fn parse_over<F: Field>(string: &str) -> Result<F, ParseError> {
+    let mut output_stack = Vec::<(F, Span)>::new();
+    for (token, span) in spanned_rpn(string, &ParserConfig::default(), None)? {
+        match token {
+            NumberOrOperator::Number(Over(n)) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(Operator::Neg) => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = span.to(n_span);
+                output_stack.push((n.neg(span)?, span));
+            }
+            NumberOrOperator::Operator(
+                o @ (Operator::Sum | Operator::Sub | Operator::Mul | Operator::Div),
+            ) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                let result = match o {
+                    Operator::Sum => n1.add(n2, span),
+                    Operator::Sub => n1.sub(n2, span),
+                    Operator::Mul => n1.mul(n2, span),
+                    _ => n1.div(n2, span),
+                };
+                output_stack.push((result?, span));
+            }
+            NumberOrOperator::Operator(o) => return Err(ParseError::DisallowedOperator(o, span)),
+        }
+    }
+
+    match output_stack.pop() {
+        Some((n, _)) => Ok(n),
+        None => F::from_str_radix("0", 10).ok_or(ParseError::Overflow(Span::default())),
+    }
+}
+
+#[test]
+fn parse_over_tests() {
+    assert_eq!(parse_over::<i32>("3a2c4"), Ok(20));
+    assert_eq!(parse_over::<i32>("3c4d2aee2a4c41fc4f"), Ok(990));
+    assert_eq!(parse_over::<i64>("3000000000c2"), Ok(6000000000));
+    assert_eq!(parse_over::<f64>("7d2"), Ok(3.5));
+    assert_eq!(parse_over::<f64>("n1d4"), Ok(-0.25));
+    assert_eq!(
+        parse_over::<Rational>("1d3a1d6").map(|n| n.to_string()),
+        Ok("2/9".to_owned())
+    );
+    assert_eq!(parse_over::<i32>(""), Ok(0));
+
+    // A field of integers modulo 7, where every nonzero number has an inverse.
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct Mod7(u8);
+
+    impl Field for Mod7 {
+        fn add(self, other: Self, _: Span) -> Result<Self, ParseError> {
+            Ok(Mod7((self.0 + other.0) % 7))
+        }
+
+        fn sub(self, other: Self, _: Span) -> Result<Self, ParseError> {
+            Ok(Mod7((self.0 + 7 - other.0) % 7))
+        }
+
+        fn mul(self, other: Self, _: Span) -> Result<Self, ParseError> {
+            Ok(Mod7(self.0 * other.0 % 7))
+        }
+
+        fn div(self, other: Self, span: Span) -> Result<Self, ParseError> {
+            let inverse = (1..7).find(|&n| n * other.0 % 7 == 1);
+            let inverse = inverse.ok_or(ParseError::DivisionByZero(span))?;
+            self.mul(Mod7(inverse), span)
+        }
+
+        fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+            let n = u64::from_str_radix(digits, radix).ok()?;
+            Some(Mod7((n % 7) as u8))
+        }
+    }
+
+    assert_eq!(parse_over::<Mod7>("3d5"), Ok(Mod7(2)));
+    assert_eq!(parse_over::<Mod7>("n1a100"), Ok(Mod7(1)));
+    assert_eq!(
+        parse_over::<Mod7>("1d14"),
+        Err(ParseError::DivisionByZero(Span::new(0, 4)))
+    );
+    assert_eq!(
+        parse_over::<i32>("2147483647a1"),
+        Err(ParseError::Overflow(Span::new(0, 12)))
+    );
+    assert_eq!(
+        parse_over::<i32>("12h18"),
+        Err(ParseError::DisallowedOperator(
+            Operator::Gcd,
+            Span::new(2, 3)
+        ))
+    );
+}
@@ -0,0 +1,186 @@
+use crate::{token::Tokens, Operator, ParseError, RoundMode, Span, Token};
+
+/// A stack backed by an array of `N` slots, so that it never allocates.
+struct FixedStack<T, const N: usize> {
+    items: [Option<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> FixedStack<T, N> {
+    fn new() -> Self {
+        Self {
+            items: [None; N],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, item: T) -> Result<(), ParseError> {
+        let slot = self
+            .items
+            .get_mut(self.len)
+            .ok_or(ParseError::CapacityExceeded)?;
+        *slot = Some(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        self.items[self.len].take()
+    }
+
+    fn last(&self) -> Option<T> {
+        self.items[..self.len].last().copied().flatten()
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but without any heap allocation:
+/// the operator stack and the operand stack have `N` slots each,
+/// and an expression that needs more returns [`ParseError::CapacityExceeded`].
+///
+/// Instead of building the whole RPN queue, every operator is applied
+/// as soon as the Shunting Yard Algorithm would move it to the output queue.
+pub fn parse_fixed<const N: usize>(string: &str) -> Result<i32, ParseError> {
+    let mut operator_stack = FixedStack::<(Operator, Span), N>::new();
+    let mut output_stack = FixedStack::<(i32, Span), N>::new();
+
+    // Applies an operator leaving the output queue to the topmost operands.
+    let output = |output_stack: &mut FixedStack<(i32, Span), N>, (o, o_span): (Operator, Span)| {
+        if o.arity() == 1 {
+            let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+            let span = o_span.to(n_span);
+            return output_stack.push((o.apply_unary(n, span)?, span));
+        }
+        if o.arity() == 3 {
+            let (n3, n3_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+            let (n2, _) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+            let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+            let span = n1_span.to(n3_span);
+            return output_stack.push((o.apply_ternary(n1, n2, n3, span)?, span));
+        }
+        let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+        let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(o_span))?;
+        let span = n1_span.to(n2_span);
+        output_stack.push((o.apply(n1, n2, RoundMode::default(), span)?, span))
+    };
+
+    // The span of the previous token, if it is a left parenthesis,
+    // and the first empty parentheses, reported once all the tokens are read.
+    let mut after_lbra = None;
+    let mut empty_parentheses = None;
+    // Whether the previous token is a binary operator.
+    let mut after_binary = false;
+    for token in Tokens::new(string) {
+        let (token, span) = token?;
+        match (after_lbra.take(), token) {
+            (Some(lbra_span), Token::Operator(Operator::RBra)) => {
+                empty_parentheses.get_or_insert(Span::to(lbra_span, span));
+            }
+            (_, Token::Operator(Operator::LBra)) => after_lbra = Some(span),
+            _ => {}
+        }
+        let is_binary = matches!(token, Token::Operator(o) if o.arity() >= 2);
+        // Operators are applied as soon as possible, so this cannot wait for the other errors.
+        if is_binary && after_binary {
+            return Err(ParseError::ConsecutiveOperators {
+                position: span.start,
+            });
+        }
+        after_binary = is_binary;
+        let o1 = match token {
+            Token::Number(n) => {
+                output_stack.push((n, span))?;
+                continue;
+            }
+            Token::Previous => return Err(ParseError::NoPreviousResult(span)),
+            Token::Operator(o) => o,
+        };
+
+        match o1 {
+            Operator::LBra | Operator::Neg | Operator::Signum => operator_stack.push((o1, span))?,
+            Operator::DigitSum | Operator::Sqrt => output(&mut output_stack, (o1, span))?,
+            Operator::RBra => loop {
+                match operator_stack.pop() {
+                    Some((Operator::LBra, bra_span))
+                        if operator_stack.last() == Some((Operator::Clamp, bra_span)) =>
+                    {
+                        return Err(ParseError::EmptyOperand(bra_span))
+                    }
+                    Some((Operator::LBra, _)) => break,
+                    Some(o) => output(&mut output_stack, o)?,
+                    None => return Err(ParseError::MissingLeftParenthesis(span)),
+                }
+            },
+            _ => {
+                while let Some(o2) = operator_stack.last().filter(|(o, _)| *o != Operator::LBra) {
+                    output(&mut output_stack, o2)?;
+                    operator_stack.pop();
+                }
+                // The lower bound of a clamp is opened by a left parenthesis with its same span,
+                // see the allocating Shunting Yard Algorithm.
+                if let Some((Operator::LBra, bra_span)) = operator_stack.last() {
+                    operator_stack.pop();
+                    if o1 == Operator::Clamp && operator_stack.last() == Some((o1, bra_span)) {
+                        continue;
+                    }
+                    operator_stack.push((Operator::LBra, bra_span))?;
+                }
+                operator_stack.push((o1, span))?;
+                if o1 == Operator::Clamp {
+                    operator_stack.push((Operator::LBra, span))?;
+                }
+            }
+        }
+    }
+
+    if let Some(span) = empty_parentheses {
+        return Err(ParseError::EmptyParentheses(span));
+    }
+    while let Some(o) = operator_stack.pop() {
+        if let (Operator::LBra, span) = o {
+            if operator_stack.last() == Some((Operator::Clamp, span)) {
+                return Err(ParseError::EmptyOperand(span));
+            }
+            return Err(ParseError::MissingRightParenthesis(span));
+        }
+        output(&mut output_stack, o)?;
+    }
+
+    if output_stack.len > 1 {
+        let (_, span) = output_stack.items[1].expect("the slot is on the stack");
+        return Err(ParseError::Malformed(span));
+    }
+    Ok(output_stack.pop().map(|(n, _)| n).unwrap_or_default())
+}
+
+#[test]
+fn parse_fixed_tests() {
+    assert_eq!(parse_fixed::<4>("3a2c4"), Ok(20));
+    assert_eq!(parse_fixed::<4>("500a10b66c32"), Ok(14208));
+    assert_eq!(parse_fixed::<4>("3c4d2aee2a4c41fc4f"), Ok(990));
+    assert_eq!(parse_fixed::<4>("15p200"), Ok(30));
+    assert_eq!(parse_fixed::<4>("3cn2"), Ok(-6));
+    assert_eq!(parse_fixed::<4>("15k0a1k10b2"), Ok(8));
+    assert_eq!(
+        parse_fixed::<4>("5k0"),
+        Err(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+    assert_eq!(parse_fixed::<4>(""), Ok(0));
+    assert_eq!(
+        parse_fixed::<4>("eeeee1fffff"),
+        Err(ParseError::CapacityExceeded)
+    );
+    assert_eq!(parse_fixed::<0>("1"), Err(ParseError::CapacityExceeded));
+    assert_eq!(
+        parse_fixed::<4>("3ae2"),
+        Err(ParseError::MissingRightParenthesis(Span::new(2, 3)))
+    );
+    assert_eq!(
+        parse_fixed::<4>("1ae3d0f"),
+        Err(ParseError::DivisionByZero(Span::new(3, 6)))
+    );
+    assert_eq!(
+        parse_fixed::<4>("2147483648"),
+        Err(ParseError::NumberOverflow(Span::new(0, 10)))
+    );
+}
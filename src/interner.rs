@@ -0,0 +1,75 @@
+use crate::{parse_ast, Expr, ParseError};
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// A cache of parsed expressions, which can be shared between threads,
+/// so that every distinct expression is parsed once, see [`ExprInterner::intern`].
+#[derive(Debug, Default)]
+pub struct ExprInterner {
+    trees: RwLock<HashMap<String, Arc<Expr>>>,
+}
+
+impl ExprInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tree of the expression, parsed with [`parse_ast`] the first time only:
+    /// the same text always gives a handle to the same tree.
+    /// Expressions that fail to parse are not cached, so their error is produced every time.
+    pub fn intern(&self, string: &str) -> Result<Arc<Expr>, ParseError> {
+        let trees = self.trees.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(expr) = trees.get(string) {
+            return Ok(Arc::clone(expr));
+        }
+        drop(trees);
+
+        // Another thread may have parsed the same expression in the meantime,
+        // and the tree it inserted is kept.
+        let expr = Arc::new(parse_ast(string)?);
+        let mut trees = self.trees.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(Arc::clone(trees.entry(string.to_owned()).or_insert(expr)))
+    }
+
+    /// The number of distinct expressions parsed so far.
+    pub fn len(&self) -> usize {
+        self.trees
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[test]
+fn expr_interner_tests() {
+    fn send_sync<T: Send + Sync>(_: &T) {}
+
+    let interner = ExprInterner::new();
+    send_sync(&interner);
+    let expr = interner.intern("3a2c4").unwrap();
+    assert_eq!(expr.eval(), Ok(20));
+    assert!(Arc::ptr_eq(&expr, &interner.intern("3a2c4").unwrap()));
+    assert_eq!(
+        interner.intern("3a"),
+        Err(ParseError::EmptyOperand(crate::Span::new(1, 2)))
+    );
+    assert_eq!(interner.len(), 1);
+
+    let trees = std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|_| scope.spawn(|| interner.intern("1ae2c3f").unwrap()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert!(trees.iter().all(|expr| Arc::ptr_eq(expr, &trees[0])));
+    assert_eq!(interner.len(), 2);
+}
@@ -0,0 +1,197 @@
+use crate::{
+    spanned_rpn, token::Literal, NumberFormat, NumberOrOperator, Operator, ParseError,
+    ParserConfig, RoundMode, Span,
+};
+
+/// A closed interval of integers, with `lo <= hi`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Interval {
+    lo: i32,
+    hi: i32,
+}
+
+impl Interval {
+    /// The smallest interval containing all the given values,
+    /// which overflows if it does not fit in an `i32`.
+    fn hull(values: [Option<i64>; 4], span: Span) -> Result<Interval, ParseError> {
+        let values = values
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseError::Overflow(span))?;
+        let bound = |n: Option<&i64>| {
+            n.and_then(|&n| i32::try_from(n).ok())
+                .ok_or(ParseError::Overflow(span))
+        };
+        Ok(Interval {
+            lo: bound(values.iter().min())?,
+            hi: bound(values.iter().max())?,
+        })
+    }
+
+    /// Applies an operator to every pair of bounds, which is enough
+    /// since all the operators are monotonic in each operand, given the sign of the other.
+    fn apply(op: Operator, n1: Interval, n2: Interval, span: Span) -> Result<Interval, ParseError> {
+        let corners = |f: fn(i64, i64) -> Option<i64>| {
+            let (a, b) = (i64::from(n1.lo), i64::from(n1.hi));
+            let (c, d) = (i64::from(n2.lo), i64::from(n2.hi));
+            [f(a, c), f(a, d), f(b, c), f(b, d)]
+        };
+        let corners = match op {
+            Operator::Sum => corners(i64::checked_add),
+            Operator::Sub => corners(i64::checked_sub),
+            Operator::Mul => corners(i64::checked_mul),
+            // The divisor could be zero, anywhere in the interval.
+            Operator::Div if n2.lo <= 0 && 0 <= n2.hi => {
+                return Err(ParseError::DivisionByZero(span))
+            }
+            Operator::Div => corners(|n1, n2| RoundMode::default().divide(n1, n2)),
+            Operator::PercentOf => corners(|n1, n2| RoundMode::default().divide(n1 * n2, 100)),
+            Operator::Gcd | Operator::Lcm | Operator::RoundTo => {
+                return Err(ParseError::DisallowedOperator(op, span))
+            }
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        Interval::hull(corners, span)
+    }
+
+    /// Applies a ternary operator, which fails if any pair of bounds is not ordered.
+    fn apply_ternary(
+        op: Operator,
+        n1: Interval,
+        n2: Interval,
+        n3: Interval,
+        span: Span,
+    ) -> Result<Interval, ParseError> {
+        match op {
+            Operator::Clamp if n2.hi > n3.lo => Err(ParseError::InvalidBounds(span)),
+            Operator::Clamp => Ok(Interval {
+                lo: n1.lo.clamp(n2.lo, n3.lo),
+                hi: n1.hi.clamp(n2.hi, n3.hi),
+            }),
+            _ => unreachable!("{op:?} is not a ternary operator"),
+        }
+    }
+
+    fn apply_unary(op: Operator, n: Interval, span: Span) -> Result<Interval, ParseError> {
+        match op {
+            Operator::Neg => Ok(Interval {
+                lo: n.hi.checked_neg().ok_or(ParseError::Overflow(span))?,
+                hi: n.lo.checked_neg().ok_or(ParseError::Overflow(span))?,
+            }),
+            // The digit sum is not monotonic, so the bounds do not give the result.
+            Operator::DigitSum => Err(ParseError::DisallowedOperator(op, span)),
+            Operator::Sqrt if n.lo < 0 => Err(ParseError::NegativeSquareRoot(span)),
+            // The square root is monotonic, so the bounds give the result.
+            Operator::Sqrt => Ok(Interval {
+                lo: n.lo.isqrt(),
+                hi: n.hi.isqrt(),
+            }),
+            Operator::Signum => Ok(Interval {
+                lo: n.lo.signum(),
+                hi: n.hi.signum(),
+            }),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        }
+    }
+}
+
+/// A number, optionally followed by a `~` and the other bound of the interval.
+impl Literal for Interval {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        let len = format.integer_len(rest);
+        match rest[len..].strip_prefix('~') {
+            Some(hi) if hi.starts_with(|c: char| c.is_ascii_digit()) => {
+                len + 1 + format.integer_len(hi)
+            }
+            _ => len,
+        }
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (lo, hi) = literal.split_once('~').unwrap_or((literal, literal));
+        let lo = i32::parse_literal(lo, format)?;
+        let hi = i32::parse_literal(hi, format)?;
+        Some(Interval {
+            lo: lo.min(hi),
+            hi: lo.max(hi),
+        })
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        let value = value.try_into().ok()?;
+        Some(Interval {
+            lo: value,
+            hi: value,
+        })
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every number literal can be an interval,
+/// like `3~5` for all the integers from `3` to `5`, and the result is the interval
+/// `(lo, hi)` of all the values the expression can take.
+/// A plain number is an interval with a single value, so `3~5a2` is `(5, 7)`,
+/// and a division fails if its divisor may be zero.
+/// The greatest common divisor, the least common multiple and the digit sum are not supported.
+pub fn parse_interval(string: &str) -> Result<(i32, i32), ParseError> {
+    let mut output_stack = Vec::<(Interval, Span)>::new();
+    for (token, span) in spanned_rpn(string, &ParserConfig::default(), None)? {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(o) if o.arity() == 1 => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = span.to(n_span);
+                output_stack.push((Interval::apply_unary(o, n, span)?, span));
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 3 => {
+                let (n3, n3_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n2, _) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n3_span);
+                output_stack.push((Interval::apply_ternary(o, n1, n2, n3, span)?, span));
+            }
+            NumberOrOperator::Operator(o) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                output_stack.push((Interval::apply(o, n1, n2, span)?, span));
+            }
+        }
+    }
+
+    let result = output_stack
+        .pop()
+        .map_or(Interval { lo: 0, hi: 0 }, |(n, _)| n);
+    Ok((result.lo, result.hi))
+}
+
+#[test]
+fn parse_interval_tests() {
+    assert_eq!(parse_interval("3~5a2"), Ok((5, 7)));
+    assert_eq!(parse_interval("3~5b1~2"), Ok((1, 4)));
+    assert_eq!(parse_interval("2~3cn1~4"), Ok((-12, -2)));
+    assert_eq!(
+        parse_interval("ne0~1b2147483647b1f"),
+        Err(ParseError::Overflow(Span::new(0, 18)))
+    );
+    assert_eq!(parse_interval("n2~3ce1b3~5f"), Ok((4, 12)));
+    assert_eq!(parse_interval("5~3"), Ok((3, 5)));
+    assert_eq!(parse_interval("0~20k5k10~15"), Ok((5, 15)));
+    assert_eq!(
+        parse_interval("0~20k5~12k10~15"),
+        Err(ParseError::InvalidBounds(Span::new(0, 15)))
+    );
+    assert_eq!(parse_interval("3a2c4"), Ok((20, 20)));
+    assert_eq!(parse_interval("10~20d2~5"), Ok((2, 10)));
+    assert_eq!(
+        parse_interval("1d0~1"),
+        Err(ParseError::DivisionByZero(Span::new(0, 5)))
+    );
+    assert_eq!(
+        parse_interval("0~2147483647a1"),
+        Err(ParseError::Overflow(Span::new(0, 14)))
+    );
+    assert_eq!(
+        parse_interval("3~a1"),
+        Err(ParseError::InvalidCharacter('~', Span::new(1, 2)))
+    );
+}
@@ -1,115 +1,1022 @@
+mod ast;
+mod bindings;
+#[cfg(feature = "complex")]
+mod complex;
+mod config;
+mod encoding;
+mod error;
+mod field;
+mod fixed;
+mod interner;
+mod interval;
+mod macros;
+mod number;
+mod random;
+mod rational;
+mod repl;
+mod semantics;
+mod span;
+#[cfg(feature = "futures")]
+mod stream;
+mod token;
+mod typed;
+
+pub use ast::{
+    affine_coeffs, ast_depth, can_be_negative, critical_path_length, eval_batch,
+    explain_precedence, parse_ast, parse_ast_with, parse_ast_with_variables, simplify, to_prefix,
+    Expr, Iter,
+};
+pub use bindings::parse_with_bindings;
+#[cfg(feature = "complex")]
+pub use complex::{parse_complex, Complex};
+pub use config::{
+    Associativity, DivByZero, NumberFormat, OperatorOverride, ParserConfig, RoundMode,
+};
+pub use encoding::{rpn_from_bytes, rpn_to_bytes};
+pub use error::{render_error, ParseError};
+pub use field::{parse_over, Field};
+pub use fixed::parse_fixed;
+pub use interner::ExprInterner;
+pub use interval::parse_interval;
+pub use macros::{enumerate_expressions, is_well_formed};
+pub use number::{
+    parse_number, parse_number_with, parse_ratio_percent, parse_ratio_percent_with, Number,
+};
+pub use random::{parse_with_rng, Rng};
+pub use rational::{parse_rational, Rational};
+pub use repl::Repl;
+pub use semantics::{
+    parse_audit, parse_divmod, parse_exact, parse_nim, parse_or, parse_or_each, parse_outcome,
+    parse_steps, parse_with_semantics, parse_wrapping_flagged, OpSemantics, ParseOutcome, Step,
+};
+pub use span::Span;
+#[cfg(feature = "futures")]
+pub use stream::parse_async;
+pub use token::{
+    distinct_operand_count, operator_histogram, reserialize, token_count, token_diff, tokenize,
+    tokenize_callback, validate_widths, Token, TokenChange,
+};
+pub use typed::parse_typed;
+
+use std::{cmp::Ordering, collections::BTreeSet, fmt, ops::Range};
+
+use token::{Literal, Tokens};
+
 /// A parser that takes a string and computes its numerical value using the given rules.
 /// Operators are applied in order of precedence from left to right.
 /// An exception to this is brackets, which are used to explicitly denote precedence
 /// by grouping parts of an expression that should be evaluated first.
 /// Rules: a = ‘+’, b = ‘-’, c = ‘*’, d = ‘/’, e = ‘(’, f = ‘)’
+///
+/// Additionally p = ‘percent of’, so `15p200` is 15% of 200, i.e. `30`,
+/// n = unary ‘-’, which negates the operand that follows it, so `3cn2` is `-6`,
+/// s = ‘signum’, which is `-1`, `0` or `1` following the sign of the operand after it, so `2csn5` is `-2`,
+/// q = ‘digit sum’, which is postfix: it replaces the operand before it with the sum of its digits,
+/// so `123q` is `6` and `123qa1` is `7`,
+/// r = ‘integer square root’, which is postfix too and rounds down, so `16r` and `17r` are `4`,
+/// h = ‘greatest common divisor’ and l = ‘least common multiple’, so `12h18` is `6` and `4l6` is `12`.
+/// The binary ones share the precedence of all the other operators, so `12h8a1` is `5`.
+/// Finally k = ‘clamp’ is ternary: `5k0k10` is `5` clamped between `0` and `10`, i.e. `5`,
+/// and `15k0k10` is `10`. The value is the operand before the first `k`,
+/// like the left operand of the binary operators, while the lower bound is evaluated
+/// as if it were enclosed in parentheses, so `5k0a1k10` is `5` clamped between `1` and `10`.
+///
+/// # Panics
+/// Panics if the expression is invalid, see [`try_parse`] for a non-panicking version.
 pub fn parse(string: &str) -> i32 {
-    // Shunting Yard Algorithm to produce a Reverse Polish Notation (RPN) expression.
-    let mut output_queue = Vec::<NumberOrOperator>::new();
-    let mut operator_stack = Vec::<Operator>::new();
-    let mut current_number = String::new();
-
-    // Helper macro to avoid code duplication;
-    // flush the current number and push it to the output queue.
-    macro_rules! flush_current_number {
-        () => {
-            if !current_number.is_empty() {
-                output_queue.push(current_number.parse::<i32>().unwrap().into());
-                current_number.clear();
+    try_parse(string).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`parse`], but returns a [`ParseError`] instead of panicking on invalid input.
+pub fn try_parse(string: &str) -> Result<i32, ParseError> {
+    try_parse_with(string, &ParserConfig::default())
+}
+
+/// Same as [`try_parse`], but the grammar is customized by the given [`ParserConfig`].
+pub fn try_parse_with(string: &str, config: &ParserConfig) -> Result<i32, ParseError> {
+    if config.wide_accumulator {
+        let (n, span) = eval_wide::<i64>(string, config)?;
+        return n.try_into().map_err(|_| ParseError::ResultOverflow(span));
+    }
+    let queue = spanned_rpn(string, config, None)?;
+    if config.stack_snapshots {
+        let mut stack = Vec::new();
+        return eval_spanned_rpn_onto(&mut stack, queue, config).map_err(|error| {
+            ParseError::WithStack {
+                error: Box::new(error),
+                stack: stack.into_iter().map(|(n, _)| n).collect(),
             }
-        };
+        });
     }
+    eval_spanned_rpn(queue, config)
+}
 
-    for c in string.chars() {
-        match c {
-            '0'..='9' => current_number.push(c),
-            'a'..='d' => {
-                flush_current_number!();
-                // While there is an operator token, o2, at the top of the operator stack
-                // which is not a left parenthesis, pop o2 off the operator stack, onto the output queue.
-                let o1 = Operator::from(c);
-                while !operator_stack.is_empty()
-                    && *operator_stack.last().unwrap() != Operator::LBra
-                {
-                    output_queue.push(operator_stack.pop().unwrap().into());
+/// Evaluates only the selected byte range of the expression, like `2c3` in `1a2c3`,
+/// reporting errors at their position in the whole expression.
+/// The selection must be a complete expression starting and ending between two tokens,
+/// or it produces [`ParseError::InvalidSelection`]: `23` is not a selection of `123a4`.
+/// A selection that cuts a pair of parentheses gives the error of the missing one.
+pub fn parse_span(string: &str, range: Range<usize>) -> Result<i32, ParseError> {
+    let span = Span::new(range.start, range.end);
+    let selection = string
+        .get(range.clone())
+        .ok_or(ParseError::InvalidSelection(span))?;
+    // Tokens after an error cannot be told apart, but the error is then in the selection
+    // or after it.
+    let cuts_token = |position| {
+        Tokens::<i32>::new(string)
+            .map_while(Result::ok)
+            .any(|(_, token)| token.start < position && position < token.end)
+    };
+    if cuts_token(range.start) || cuts_token(range.end) {
+        return Err(ParseError::InvalidSelection(span));
+    }
+    try_parse(selection).map_err(|err| err.shifted(range.start))
+}
+
+/// Same as [`try_parse`], but the error carries a copy of the expression,
+/// available with [`ParseError::input`].
+///
+/// ```
+/// let err = arithmetic_parser::try_parse_ctx("3d0").unwrap_err();
+/// assert_eq!(err.input(), Some("3d0"));
+/// assert_eq!(err.to_string(), "Division by zero at 0..3 in '3d0'");
+/// ```
+pub fn try_parse_ctx(string: &str) -> Result<i32, ParseError> {
+    try_parse(string).map_err(|err| err.with_input(string))
+}
+
+/// Same as [`try_parse`], but intermediate results and the result are `i64`,
+/// while literals are still `i32`, so `2147483647c2` is `4294967294`.
+/// An operation whose result does not fit in an `i64` still overflows.
+pub fn parse_wide(string: &str) -> Result<i64, ParseError> {
+    eval_wide::<i32>(string, &ParserConfig::default()).map(|(n, _)| n)
+}
+
+/// Evaluates an expression with literals of type `N` and `i64` intermediate results,
+/// returning the result with its span.
+fn eval_wide<N: Literal + Into<i64>>(
+    string: &str,
+    config: &ParserConfig,
+) -> Result<(i64, Span), ParseError> {
+    let queue =
+        spanned_rpn::<N>(string, config, None)?
+            .into_iter()
+            .map(|(item, span)| match item {
+                NumberOrOperator::Number(n) => {
+                    (NumberOrOperator::Number(Number::Int(n.into())), span)
                 }
-                // At the end of iteration push o1 onto the operator stack.
-                operator_stack.push(o1);
+                NumberOrOperator::Operator(o) => (o.into(), span),
+            });
+    match number::eval_numbers(queue, config)? {
+        (Number::Int(n), span) => Ok((n, span)),
+        (Number::Float(_), _) => unreachable!("integer literals evaluate to integers"),
+    }
+}
+
+/// Converts the tokens of an expression, as returned by [`tokenize`],
+/// into Reverse Polish Notation (RPN).
+///
+/// Since the tokens do not carry their position in the source,
+/// the spans of the errors are the indices of the tokens:
+/// the token at index `i` spans `i..i + 1`.
+pub fn to_rpn(tokens: &[Token]) -> Result<Vec<NumberOrOperator>, ParseError> {
+    let tokens = (0..)
+        .zip(tokens)
+        .map(|(i, &token)| Ok((token, Span::new(i, i + 1))));
+    let queue = shunting_yard(tokens, &ParserConfig::default(), None)?;
+    Ok(queue.into_iter().map(|(item, _)| item).collect())
+}
+
+/// Evaluates an expression in Reverse Polish Notation (RPN), as returned by [`to_rpn`].
+///
+/// Since the queue does not carry positions in the source,
+/// the spans of the errors are indices of the queue:
+/// the item at index `i` spans `i..i + 1`, and an operation spans all its operands.
+/// A queue whose values do not combine into a single one is [`ParseError::Malformed`].
+pub fn eval_rpn(queue: &[NumberOrOperator]) -> Result<i32, ParseError> {
+    let queue = (0..)
+        .zip(queue)
+        .map(|(i, &item)| (item, Span::new(i, i + 1)))
+        .collect::<Vec<_>>();
+    check_operands(&queue)?;
+    eval_spanned_rpn(queue, &ParserConfig::default())
+}
+
+/// Evaluates an expression in prefix (Polish) notation, as returned by [`to_prefix`],
+/// reading it from the end, so that the operands of an operator are evaluated before it.
+/// The spans of the errors are indices of the items, like with [`eval_rpn`].
+pub fn eval_prefix(items: &[NumberOrOperator]) -> Result<i32, ParseError> {
+    let config = ParserConfig::default();
+    let mut output_stack = Vec::<(i32, Span)>::new();
+    for (i, &item) in items.iter().enumerate().rev() {
+        let span = Span::new(i, i + 1);
+        let o = match item {
+            NumberOrOperator::Number(n) => {
+                output_stack.push((n, span));
+                continue;
+            }
+            NumberOrOperator::Operator(o) => o,
+        };
+        // Parentheses only group operations, so they cannot be items, like in [`eval_rpn`].
+        let arity = match o.arity() {
+            0 => return Err(ParseError::Malformed(span)),
+            arity => arity,
+        };
+        let start = output_stack
+            .len()
+            .checked_sub(arity)
+            .ok_or(ParseError::EmptyOperand(span))?;
+        // The top of the stack is the first operand.
+        let result = match output_stack[start..] {
+            [(n, n_span)] => {
+                let span = span.to(n_span);
+                (config.apply_unary(o, n, span)?, span)
+            }
+            [(n3, n3_span), (n2, _), (n1, _)] => {
+                let span = span.to(n3_span);
+                (config.apply_ternary(o, n1, n2, n3, span)?, span)
+            }
+            [(n2, n2_span), (n1, _)] => {
+                let span = span.to(n2_span);
+                (config.apply(o, n1, n2, span)?, span)
+            }
+            _ => unreachable!("the operator has {arity} operands"),
+        };
+        output_stack.truncate(start);
+        output_stack.push(result);
+    }
+
+    match output_stack[..] {
+        [] => Ok(0),
+        [(n, _)] => Ok(n),
+        [.., (_, span), _] => Err(ParseError::Malformed(span)),
+    }
+}
+
+/// Joins the items of an expression in Reverse Polish Notation (RPN) with spaces,
+/// so the queue of `3a2c4` is `3 2 + 4 *`.
+pub fn rpn_to_string<N: fmt::Display>(queue: &[NumberOrOperator<N>]) -> String {
+    queue
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Converts an expression into Reverse Polish Notation (RPN),
+/// where every item is paired with its position in the source.
+/// The `$` token stands for the `previous` result, if any.
+fn spanned_rpn<N: Literal>(
+    string: &str,
+    config: &ParserConfig,
+    previous: Option<N>,
+) -> Result<Vec<(NumberOrOperator<N>, Span)>, ParseError> {
+    shunting_yard(Tokens::with_config(string, config), config, previous)
+}
+
+/// Shunting Yard Algorithm to produce a Reverse Polish Notation (RPN) expression.
+fn shunting_yard<N: Literal>(
+    tokens: impl IntoIterator<Item = Result<(Token<N>, Span), ParseError>>,
+    config: &ParserConfig,
+    previous: Option<N>,
+) -> Result<Vec<(NumberOrOperator<N>, Span)>, ParseError> {
+    let mut output_queue = Vec::<(NumberOrOperator<N>, Span)>::new();
+    let mut operator_stack = Vec::<(Operator, Span)>::new();
+    // Whether the tokens so far are a complete expression, for strict mode.
+    let mut complete = false;
+    let mut depth = 0usize;
+    // The span of the previous token, if it is a left parenthesis.
+    let mut after_lbra = None;
+    // Whether the previous token is a binary operator.
+    let mut after_binary = false;
+    // The first empty parentheses or consecutive binary operators,
+    // reported only if there is no other error in the tokens.
+    let mut deferred = None;
+
+    for token in tokens {
+        let (token, span) = match token {
+            Err(err) if config.strict && complete => {
+                let position = err.span().map_or(0, |span| span.start);
+                return Err(ParseError::TrailingInput { position });
+            }
+            token => token?,
+        };
+        if config.strict {
+            let continues = matches!(
+                token,
+                Token::Operator(o) if o.arity() >= 2 || o == Operator::RBra || o.is_postfix()
+            );
+            if complete && !continues {
+                return Err(ParseError::TrailingInput {
+                    position: span.start,
+                });
+            }
+            match token {
+                Token::Operator(Operator::LBra) => depth += 1,
+                Token::Operator(Operator::RBra) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            complete = depth == 0
+                && matches!(
+                    token,
+                    Token::Number(_)
+                        | Token::Previous
+                        | Token::Operator(Operator::RBra | Operator::DigitSum | Operator::Sqrt)
+                );
+        }
+        let lbra_span = after_lbra.take();
+        if let Token::Operator(Operator::LBra) = token {
+            after_lbra = Some(span);
+        }
+        let is_binary = matches!(token, Token::Operator(o) if o.arity() >= 2);
+        if is_binary && after_binary {
+            deferred.get_or_insert(ParseError::ConsecutiveOperators {
+                position: span.start,
+            });
+        }
+        after_binary = is_binary;
+        let o1 = match token {
+            Token::Number(n) => {
+                output_queue.push((NumberOrOperator::Number(n), span));
+                continue;
             }
-            'e' => operator_stack.push(Operator::from(c).into()),
-            'f' => {
-                flush_current_number!();
+            Token::Previous => {
+                let previous = previous.ok_or(ParseError::NoPreviousResult(span))?;
+                output_queue.push((NumberOrOperator::Number(previous), span));
+                continue;
+            }
+            Token::Operator(o) => o,
+        };
+        if !config.allowed_operators.contains(&o1) {
+            return Err(ParseError::DisallowedOperator(o1, span));
+        }
+
+        match o1 {
+            Operator::LBra => operator_stack.push((o1, span)),
+            // A prefix operator applies to the operand that follows,
+            // so it never pops anything off the operator stack.
+            Operator::Neg | Operator::Signum => operator_stack.push((o1, span)),
+            // A postfix operator applies to the operand before it, which is already complete,
+            // so it goes straight to the output queue.
+            Operator::DigitSum | Operator::Sqrt => output_queue.push((o1.into(), span)),
+            Operator::RBra => {
+                match lbra_span {
+                    Some(lbra_span) if !config.allow_empty_parentheses => {
+                        deferred.get_or_insert(ParseError::EmptyParentheses(lbra_span.to(span)));
+                    }
+                    Some(_) => {
+                        let zero = N::from_constant(0).expect("zero is in the range of literals");
+                        output_queue.push((NumberOrOperator::Number(zero), span));
+                    }
+                    None => {}
+                }
                 // Until the token at the top of the stack is a left parenthesis,
                 // pop operators off the stack onto the output queue.
-                while !operator_stack.is_empty()
-                    && *operator_stack.last().unwrap() != Operator::LBra
-                {
-                    output_queue.push(operator_stack.pop().unwrap().into());
+                loop {
+                    match operator_stack.pop() {
+                        Some((Operator::LBra, bra_span)) if is_clamp(&operator_stack, bra_span) => {
+                            return Err(ParseError::EmptyOperand(bra_span))
+                        }
+                        Some((Operator::LBra, _)) => break,
+                        Some((o, o_span)) => output_queue.push((o.into(), o_span)),
+                        None => return Err(ParseError::MissingLeftParenthesis(span)),
+                    }
                 }
-                if operator_stack.is_empty() {
-                    panic!("Invalid expression, missing left parenthesis");
+            }
+            _ => {
+                // While there is an operator token, o2, at the top of the operator stack
+                // which is not a left parenthesis, and o2 has greater precedence than o1,
+                // or the same precedence and o1 is left associative,
+                // pop o2 off the operator stack, onto the output queue.
+                while let Some(&(o2, o2_span)) = operator_stack.last() {
+                    if o2 == Operator::LBra || !config.applies_before(o2, o1) {
+                        break;
+                    }
+                    output_queue.push((o2.into(), o2_span));
+                    operator_stack.pop();
+                }
+                match operator_stack.last() {
+                    // The second `k` of a clamp closes its lower bound.
+                    Some(&(Operator::LBra, bra_span))
+                        if o1 == Operator::Clamp
+                            && is_clamp(&operator_stack[..operator_stack.len() - 1], bra_span) =>
+                    {
+                        operator_stack.pop();
+                    }
+                    // At the end of iteration push o1 onto the operator stack.
+                    _ => {
+                        operator_stack.push((o1, span));
+                        // The first `k` of a clamp opens its lower bound like a left parenthesis,
+                        // with the same span, so that the two can be told apart.
+                        if o1 == Operator::Clamp {
+                            operator_stack.push((Operator::LBra, span));
+                        }
+                    }
                 }
-                // Pop the left parenthesis from the stack, but not onto the output queue.
-                operator_stack.pop();
             }
-            _ => panic!("Invalid character"),
         }
     }
 
-    // We need to flush the last number of the operation, if any.
-    flush_current_number!();
-
     // When there are no more tokens to read, while there are still operator tokens in the stack:
     // if the operator token on the top of the stack is a parenthesis, then there are mismatched parentheses.
-    while !operator_stack.is_empty() {
-        let ope = operator_stack.pop().unwrap();
+    while let Some((ope, ope_span)) = operator_stack.pop() {
+        if ope == Operator::LBra && is_clamp(&operator_stack, ope_span) {
+            return Err(ParseError::EmptyOperand(ope_span));
+        }
         if ope == Operator::LBra {
-            panic!("Invalid expression, missing right parenthesis");
+            return Err(ParseError::MissingRightParenthesis(ope_span));
         }
-        output_queue.push(ope.into());
+        output_queue.push((ope.into(), ope_span));
     }
 
-    // Now the output queue is in RPN, we can evaluate it.
-    let mut output_stack = Vec::<i32>::new();
-    while !output_queue.is_empty() {
-        match output_queue.remove(0) {
-            NumberOrOperator::Number(n) => output_stack.push(n),
+    if let Some(err) = deferred {
+        return Err(err);
+    }
+    check_operands(&output_queue)?;
+    Ok(output_queue)
+}
+
+/// Checks that every operator of an expression in Reverse Polish Notation has its operands,
+/// and that they all combine into a single value, before the expression is evaluated.
+/// The operands on the stack are tracked by their spans only.
+fn check_operands<N>(queue: &[(NumberOrOperator<N>, Span)]) -> Result<(), ParseError> {
+    let mut operands = Vec::<Span>::new();
+    for &(ref token, span) in queue {
+        let span = match *token {
+            NumberOrOperator::Number(_) => span,
+            // Parentheses are not operators of a queue, since they only group operations.
+            NumberOrOperator::Operator(o) if o.arity() == 0 => {
+                return Err(ParseError::Malformed(span))
+            }
             NumberOrOperator::Operator(o) => {
-                let n2 = output_stack.pop().unwrap();
-                let n1 = output_stack.pop().unwrap();
-                let result = match o {
-                    Operator::Sum => n1 + n2,
-                    Operator::Sub => n1 - n2,
-                    Operator::Mul => n1 * n2,
-                    Operator::Div => n1 / n2,
-                    _ => panic!("Invalid operator"),
+                let arity = o.arity();
+                let start = operands
+                    .len()
+                    .checked_sub(arity)
+                    .ok_or(ParseError::EmptyOperand(span))?;
+                let span = match operands[start..] {
+                    [n_span] => span.to(n_span),
+                    [first, .., last] => first.to(last),
+                    [] => span,
                 };
-                output_stack.push(result);
+                operands.truncate(start);
+                span
+            }
+        };
+        operands.push(span);
+    }
+    match operands[..] {
+        [] | [_] => Ok(()),
+        [_, second, ..] => Err(ParseError::Malformed(second)),
+    }
+}
+
+/// Whether a left parenthesis with the given span, just above the top of the operator stack,
+/// is the lower bound of a clamp, i.e. the clamp below it has the same span.
+fn is_clamp(operator_stack: &[(Operator, Span)], bra_span: Span) -> bool {
+    operator_stack.last() == Some(&(Operator::Clamp, bra_span))
+}
+
+/// Evaluates an expression in Reverse Polish Notation (RPN), where every item has a span,
+/// applying the operators with the given semantics.
+/// Every value on the stack keeps the span of the subexpression it comes from,
+/// so that evaluation errors can point to the failing operation.
+fn eval_spanned_rpn(
+    queue: impl IntoIterator<Item = (NumberOrOperator, Span)>,
+    semantics: &impl OpSemantics,
+) -> Result<i32, ParseError> {
+    eval_spanned_rpn_onto(&mut Vec::new(), queue, semantics)
+}
+
+/// Same as [`eval_spanned_rpn`], but on the given stack, where the operands of an operator
+/// are only removed once it is applied, so that on error the stack is left as it was.
+fn eval_spanned_rpn_onto(
+    output_stack: &mut Vec<(i32, Span)>,
+    queue: impl IntoIterator<Item = (NumberOrOperator, Span)>,
+    semantics: &impl OpSemantics,
+) -> Result<i32, ParseError> {
+    for (token, span) in queue {
+        let o = match token {
+            NumberOrOperator::Number(n) => {
+                output_stack.push((n, span));
+                continue;
+            }
+            NumberOrOperator::Operator(o) => o,
+        };
+        let arity = match o.arity() {
+            1 => 1,
+            3 => 3,
+            _ => 2,
+        };
+        let start = output_stack
+            .len()
+            .checked_sub(arity)
+            .ok_or(ParseError::EmptyOperand(span))?;
+        let result = match output_stack[start..] {
+            [(n, n_span)] => {
+                let span = span.to(n_span);
+                (semantics.apply_unary(o, n, span)?, span)
+            }
+            [(n1, n1_span), (n2, _), (n3, n3_span)] => {
+                let span = n1_span.to(n3_span);
+                (semantics.apply_ternary(o, n1, n2, n3, span)?, span)
+            }
+            [(n1, n1_span), (n2, n2_span)] => {
+                let span = n1_span.to(n2_span);
+                (semantics.apply(o, n1, n2, span)?, span)
+            }
+            _ => unreachable!("the operator has {arity} operands"),
+        };
+        output_stack.truncate(start);
+        output_stack.push(result);
+    }
+
+    Ok(output_stack.last().map(|&(n, _)| n).unwrap_or_default())
+}
+
+/// Parses the longest prefix of the string that is a complete expression,
+/// returning its value and its length in bytes, so that `3a2;extra` gives `(5, 3)`.
+/// See [`ParserConfig::strict`] for when an expression is complete.
+pub fn parse_prefix(string: &str) -> Result<(i32, usize), ParseError> {
+    let config = ParserConfig {
+        strict: true,
+        ..Default::default()
+    };
+    match try_parse_with(string, &config) {
+        Err(ParseError::TrailingInput { position }) => {
+            try_parse(&string[..position]).map(|n| (n, position))
+        }
+        result => result.map(|n| (n, string.len())),
+    }
+}
+
+/// Same as [`try_parse`], but an expression which is a product of factors,
+/// one of which is the literal `0`, like `5c0c7c9`, is `0` without evaluating the others.
+/// Since operators are applied from left to right, this is only certain
+/// once the whole expression is read, and the operators outside of parentheses are all `c`.
+///
+/// The other factors are not evaluated, so a division by zero or an overflow in any of them
+/// is not reported: `e1d0fc0` is `0`. Any other expression is evaluated as usual.
+pub fn parse_product_shortcircuit(string: &str) -> Result<i32, ParseError> {
+    let mut depth = 0usize;
+    // The number of tokens of the factor being read, and whether it is just a `0`.
+    let mut factor_len = 0;
+    let mut factor_is_zero = false;
+    let mut product_is_zero = false;
+    for token in Tokens::<i32>::new(string) {
+        let (token, _) = token?;
+        match token {
+            Token::Operator(Operator::Mul) if depth == 0 => {
+                if factor_len == 0 {
+                    return try_parse(string);
+                }
+                product_is_zero |= factor_len == 1 && factor_is_zero;
+                factor_len = 0;
+                continue;
+            }
+            Token::Operator(o) if depth == 0 && o.arity() >= 2 => return try_parse(string),
+            Token::Operator(Operator::LBra) => depth += 1,
+            Token::Operator(Operator::RBra) if depth == 0 => return try_parse(string),
+            Token::Operator(Operator::RBra) => depth -= 1,
+            _ => {}
+        }
+        factor_is_zero = token == Token::Number(0);
+        factor_len += 1;
+    }
+    product_is_zero |= factor_len == 1 && factor_is_zero;
+
+    if product_is_zero && depth == 0 && factor_len > 0 {
+        Ok(0)
+    } else {
+        try_parse(string)
+    }
+}
+
+/// Whether an expression has any parenthesis, `e` or `f`.
+pub fn contains_parens(string: &str) -> bool {
+    string.contains(['e', 'f'])
+}
+
+/// Same as [`try_parse`], but with the conventional precedence of math,
+/// see [`ParserConfig::with_conventional_precedence`], so `3a2c4` is `11`.
+///
+/// An expression without parentheses made only of numbers, `a`, `b`, `c` and `d`
+/// takes a fast path, which evaluates the products and quotients on the fly,
+/// adding up the terms as soon as they are complete, without the Shunting Yard Algorithm.
+/// The operations are applied in the same order, so the results and the errors are the same.
+/// An expression with a syntax error anywhere does not take the fast path,
+/// so that this error is reported, even if an operation before it fails.
+pub fn parse_with_precedence(string: &str) -> Result<i32, ParseError> {
+    if !contains_parens(string) {
+        if let Some(result) = eval_terms(string) {
+            return result;
+        }
+    }
+    try_parse_with(
+        string,
+        &ParserConfig::default().with_conventional_precedence(),
+    )
+}
+
+/// The fast path of [`parse_with_precedence`], `None` if the expression does not qualify.
+/// The whole expression is lexed and checked first, so that a syntax error anywhere
+/// is reported by the slow path, as it would be without the fast path.
+fn eval_terms(string: &str) -> Option<Result<i32, ParseError>> {
+    let tokens = Tokens::<i32>::new(string)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if tokens.len() % 2 == 0 {
+        return None;
+    }
+    // Numbers and operators alternate, from a number to a number.
+    let mut numbers = Vec::with_capacity(tokens.len() / 2 + 1);
+    let mut operators = Vec::with_capacity(tokens.len() / 2);
+    for (i, (token, span)) in tokens.into_iter().enumerate() {
+        match token {
+            Token::Number(n) if i % 2 == 0 => numbers.push((n, span)),
+            Token::Operator(
+                o @ (Operator::Sum | Operator::Sub | Operator::Mul | Operator::Div),
+            ) if i % 2 == 1 => operators.push(o),
+            _ => return None,
+        }
+    }
+    let apply = |o: Operator, (n1, n1_span): (i32, Span), (n2, n2_span): (i32, Span)| {
+        let span = n1_span.to(n2_span);
+        o.apply(n1, n2, RoundMode::default(), span)
+            .map(|n| (n, span))
+    };
+
+    // The terms added up so far, with the operator adding the term being read.
+    let mut sum = None;
+    let mut numbers = numbers.into_iter();
+    let mut term = numbers.next()?;
+    for (o, next) in operators.into_iter().zip(numbers) {
+        match o {
+            Operator::Mul | Operator::Div => match apply(o, term, next) {
+                Ok(product) => term = product,
+                Err(err) => return Some(Err(err)),
+            },
+            _ => {
+                if let Some((sum_op, partial)) = sum.take() {
+                    match apply(sum_op, partial, term) {
+                        Ok(partial) => term = partial,
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+                sum = Some((o, term));
+                term = next;
+            }
+        }
+    }
+    Some(match sum {
+        Some((sum_op, partial)) => apply(sum_op, partial, term).map(|(n, _)| n),
+        None => Ok(term.0),
+    })
+}
+
+/// The running values of an expression: its first number, then the top of the stack
+/// after every operator is applied, in evaluation order, so `10a5b3` gives `[10, 15, 12]`.
+/// Within parentheses these are the values of the subexpressions, so `2ce3a4f` gives `[2, 7, 14]`.
+pub fn running_values(string: &str) -> Result<Vec<i32>, ParseError> {
+    let config = ParserConfig::default();
+    let mut stack = Vec::new();
+    let mut values = Vec::new();
+    for item in spanned_rpn(string, &config, None)? {
+        let is_operator = matches!(item.0, NumberOrOperator::Operator(_));
+        let value = eval_spanned_rpn_onto(&mut stack, [item], &config)?;
+        if is_operator || values.is_empty() {
+            values.push(value);
+        }
+    }
+    Ok(values)
+}
+
+/// Evaluates `inner`, and then `outer` where every `placeholder` stands for its value,
+/// like `$` does, so with the placeholder `?` an outer `?c2` and an inner `3a4` give `14`.
+/// The placeholder is an operand on its own, even next to digits, so `1?` is malformed.
+/// The spans of the errors are positions in the expression they come from.
+pub fn parse_compose(outer: &str, placeholder: char, inner: &str) -> Result<i32, ParseError> {
+    let value = try_parse(inner)?;
+    let config = ParserConfig::default();
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for (i, piece) in outer.split(placeholder).enumerate() {
+        if i > 0 {
+            let span = Span::new(offset - placeholder.len_utf8(), offset);
+            tokens.push(Ok((Token::Previous, span)));
+        }
+        tokens.extend(Tokens::new(piece).map(|token| match token {
+            Ok((token, span)) => Ok((token, Span::new(span.start + offset, span.end + offset))),
+            Err(err) => Err(err.shifted(offset)),
+        }));
+        offset += piece.len() + placeholder.len_utf8();
+    }
+    eval_spanned_rpn(shunting_yard(tokens, &config, Some(value))?, &config)
+}
+
+/// Same as [`try_parse`], but unbalanced parentheses are fixed to preview partial input:
+/// the missing right parentheses are added at the end, so `3ae2` is `3ae2f`,
+/// and right parentheses that close nothing are ignored, so `f3a2` is `5`.
+pub fn parse_lenient(string: &str) -> Result<i32, ParseError> {
+    let config = ParserConfig::default();
+    let mut depth = 0usize;
+    let mut tokens = Vec::new();
+    for token in Tokens::new(string) {
+        match token {
+            Ok((Token::Operator(Operator::LBra), _)) => depth += 1,
+            Ok((Token::Operator(Operator::RBra), _)) if depth == 0 => continue,
+            Ok((Token::Operator(Operator::RBra), _)) => depth -= 1,
+            _ => {}
+        }
+        tokens.push(token);
+    }
+    let end = Span::new(string.len(), string.len());
+    tokens.extend((0..depth).map(|_| Ok((Token::Operator(Operator::RBra), end))));
+    eval_spanned_rpn(shunting_yard(tokens, &config, None)?, &config)
+}
+
+/// Same as [`try_parse`], but parentheses are ignored, so the operators are always applied
+/// from left to right: `3ce4a2f` is `14` rather than `18`, like in legacy systems
+/// that stripped the parentheses. Unbalanced parentheses are ignored too.
+pub fn parse_flat(string: &str) -> Result<i32, ParseError> {
+    let config = ParserConfig::default();
+    let tokens = Tokens::new(string).filter(|token| {
+        !matches!(
+            token,
+            Ok((Token::Operator(Operator::LBra | Operator::RBra), _))
+        )
+    });
+    eval_spanned_rpn(shunting_yard(tokens, &config, None)?, &config)
+}
+
+/// Same as [`try_parse`], but the result is formatted with the given separator
+/// between every group of three digits, so `500a10b66c32` gives `14,208` with `,`.
+pub fn parse_formatted(string: &str, sep: char) -> Result<String, ParseError> {
+    let n = try_parse(string)?;
+    let digits = n.unsigned_abs().to_string();
+    let mut formatted = String::with_capacity(digits.len() * 2);
+    if n < 0 {
+        formatted.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            formatted.push(sep);
+        }
+        formatted.push(digit);
+    }
+    Ok(formatted)
+}
+
+/// Same as [`try_parse`], but the result is written in Roman numerals, so `1990a4` gives `MCMXCIV`.
+/// Only results from `1` to `3999` can be written, any other one produces [`ParseError::NotRoman`].
+pub fn parse_roman(string: &str) -> Result<String, ParseError> {
+    const NUMERALS: [(i32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut n = try_parse(string)?;
+    if !(1..=3999).contains(&n) {
+        return Err(ParseError::NotRoman(n));
+    }
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    Ok(roman)
+}
+
+/// The terms of an expression, i.e. the substrings between its `a` and `b` operators
+/// outside of any parentheses, so `1ae2b3fb4c5` gives `["1", "e2b3f", "4c5"]`.
+/// With the [conventional precedence](ParserConfig::with_conventional_precedence)
+/// every term can be evaluated on its own, before adding up the results.
+/// The expression is only tokenized, so a term may not be a valid expression,
+/// but unbalanced parentheses produce the same errors as with [`try_parse`].
+pub fn top_level_terms(string: &str) -> Result<Vec<String>, ParseError> {
+    let mut terms = Vec::new();
+    let mut open = Vec::new();
+    let mut start = 0;
+    for token in Tokens::<i32>::new(string) {
+        let (token, span) = token?;
+        match token {
+            Token::Operator(Operator::LBra) => open.push(span),
+            Token::Operator(Operator::RBra) => {
+                open.pop().ok_or(ParseError::MissingLeftParenthesis(span))?;
+            }
+            Token::Operator(Operator::Sum | Operator::Sub) if open.is_empty() => {
+                terms.push(string[start..span.start].to_owned());
+                start = span.end;
+            }
+            _ => {}
+        }
+    }
+    if let Some(&span) = open.first() {
+        return Err(ParseError::MissingRightParenthesis(span));
+    }
+    if !string.is_empty() {
+        terms.push(string[start..].to_owned());
+    }
+    Ok(terms)
+}
+
+/// Removes the redundant parentheses of an expression, i.e. those around a single number
+/// or around a single parenthesized group, so `eee3fffae1a2f` gives `3ae1a2f`.
+/// The other parentheses are kept, even if they do not change the value, like in `e1a2fa3`.
+/// The expression must be valid, or it produces the same error as [`try_parse`]
+/// before it is evaluated.
+pub fn collapse_parens(string: &str) -> Result<String, ParseError> {
+    spanned_rpn::<i32>(string, &ParserConfig::default(), None)?;
+    let tokens = Tokens::<i32>::new(string).collect::<Result<Vec<_>, _>>()?;
+
+    // The index of the matching right parenthesis of every left parenthesis.
+    let mut matching = vec![None; tokens.len()];
+    let mut open = Vec::new();
+    for (i, (token, _)) in tokens.iter().enumerate() {
+        match token {
+            Token::Operator(Operator::LBra) => open.push(i),
+            Token::Operator(Operator::RBra) => {
+                let start = open.pop().expect("the parentheses are balanced");
+                matching[start] = Some(i);
             }
+            _ => {}
         }
     }
 
-    output_stack.pop().unwrap_or_default()
+    let mut removed = vec![false; tokens.len()];
+    for (start, end) in matching.iter().enumerate() {
+        let Some(end) = *end else { continue };
+        let redundant = match tokens[start + 1].0 {
+            Token::Number(_) | Token::Previous => end == start + 2,
+            Token::Operator(Operator::LBra) => matching[start + 1] == Some(end - 1),
+            Token::Operator(_) => false,
+        };
+        if redundant {
+            removed[start] = true;
+            removed[end] = true;
+        }
+    }
+    Ok(tokens
+        .iter()
+        .zip(removed)
+        .filter(|(_, removed)| !removed)
+        .map(|((_, span), _)| &string[span.start..span.end])
+        .collect())
+}
+
+/// Evaluates the expressions found in free text, in order.
+/// An expression is a run of digits and operator characters which contains at least a digit,
+/// any other character separates expressions,
+/// so `result: 3a2, then 4c5` gives `5` and `20`, while the words are ignored.
+/// The spans of the errors are positions in the whole text.
+pub fn extract_expressions(text: &str) -> Vec<Result<i32, ParseError>> {
+    let is_expression_char = |c: char| c.is_ascii_digit() || Operator::from_char(c).is_some();
+    let mut results = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(is_expression_char) {
+        let len = rest[start..]
+            .find(|c| !is_expression_char(c))
+            .unwrap_or(rest.len() - start);
+        let candidate = &rest[start..start + len];
+        if candidate.contains(|c: char| c.is_ascii_digit()) {
+            let offset = text.len() - rest.len() + start;
+            results.push(try_parse(candidate).map_err(|err| err.shifted(offset)));
+        }
+        rest = &rest[start + len..];
+    }
+    results
+}
+
+/// The sign of the value of an expression, compared to zero: `3b5` is [`Ordering::Less`].
+/// Literals and intermediate results are `i64`, like with the
+/// [`wide_accumulator`](ParserConfig::wide_accumulator), so the value need not fit in an `i32`.
+pub fn result_sign(string: &str) -> Result<Ordering, ParseError> {
+    let (n, _) = eval_wide::<i64>(string, &ParserConfig::default())?;
+    Ok(n.cmp(&0))
+}
+
+/// Tells whether two expressions are semantically equal.
+/// Since every expression of the grammar is made of constants only,
+/// two expressions are equal when they evaluate to the same value:
+/// `3a2` and `2a3` are equal, and so are `2c3` and `6`.
+pub fn semantically_equal(a: &str, b: &str) -> Result<bool, ParseError> {
+    Ok(try_parse(a)? == try_parse(b)?)
+}
+
+/// Tells whether two parenthesizations of the same expression have the same value:
+/// `e1a2fa3` and `1ae2a3f` do, while `e1b2fb3` and `1be2b3f` do not.
+/// Expressions which differ in anything but their parentheses, like `1a2` and `2a1`,
+/// are not parenthesizations of each other, so they are never equal.
+pub fn parenthesizations_equal(a: &str, b: &str) -> Result<bool, ParseError> {
+    let without_parentheses = |string| {
+        Tokens::<i32>::new(string)
+            .map(|token| token.map(|(token, _)| token))
+            .filter(|token| !matches!(token, Ok(Token::Operator(Operator::LBra | Operator::RBra))))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let (value_a, value_b) = (try_parse(a)?, try_parse(b)?);
+    Ok(without_parentheses(a)? == without_parentheses(b)? && value_a == value_b)
 }
 
-pub enum NumberOrOperator {
-    Number(i32),
+/// The greatest number of operands of [`all_parenthesized_results`],
+/// whose count of parenthesizations grows exponentially.
+const MAX_PARENTHESIZED_OPERANDS: usize = 12;
+
+/// The distinct values of all the ways to fully parenthesize an expression without parentheses,
+/// so `1b2b3` gives `-4` for `e1b2fb3` and `2` for `1be2b3f`.
+/// A grouping whose evaluation fails, like a division by zero, has no value.
+/// Only binary operators are supported, any other operator produces
+/// [`ParseError::DisallowedOperator`], and an expression with more than `12` operands
+/// produces [`ParseError::CapacityExceeded`].
+pub fn all_parenthesized_results(string: &str) -> Result<BTreeSet<i32>, ParseError> {
+    spanned_rpn::<i32>(string, &ParserConfig::default(), None)?;
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    for token in Tokens::<i32>::new(string) {
+        match token? {
+            (Token::Number(n), span) => operands.push((n, span)),
+            (Token::Operator(o), _) if o.arity() == 2 => operators.push(o),
+            (Token::Operator(o), span) => return Err(ParseError::DisallowedOperator(o, span)),
+            (Token::Previous, span) => return Err(ParseError::NoPreviousResult(span)),
+        }
+    }
+    if operands.is_empty() {
+        return Ok(BTreeSet::from([0]));
+    }
+    if operands.len() > MAX_PARENTHESIZED_OPERANDS {
+        return Err(ParseError::CapacityExceeded);
+    }
+
+    // The values of the operands from `i` to `j`, for every range of them from the shortest.
+    let n = operands.len();
+    let mut values = vec![vec![BTreeSet::new(); n]; n];
+    for (i, &(operand, _)) in operands.iter().enumerate() {
+        values[i][i].insert(operand);
+    }
+    for len in 2..=n {
+        for i in 0..=n - len {
+            let j = i + len - 1;
+            let span = operands[i].1.to(operands[j].1);
+            let mut results = BTreeSet::new();
+            // The operator between the operands `k` and `k + 1` is applied last.
+            for k in i..j {
+                for &n1 in &values[i][k] {
+                    for &n2 in &values[k + 1][j] {
+                        if let Ok(n) = operators[k].apply(n1, n2, RoundMode::default(), span) {
+                            results.insert(n);
+                        }
+                    }
+                }
+            }
+            values[i][j] = results;
+        }
+    }
+    Ok(std::mem::take(&mut values[0][n - 1]))
+}
+
+/// An item of an expression in Reverse Polish Notation (RPN), whose numbers are of type `N`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NumberOrOperator<N = i32> {
+    Number(N),
     Operator(Operator),
 }
 
+/// The number, or the symbol of the operator.
+impl<N: fmt::Display> fmt::Display for NumberOrOperator<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberOrOperator::Number(n) => write!(f, "{n}"),
+            NumberOrOperator::Operator(o) => write!(f, "{o}"),
+        }
+    }
+}
+
 impl From<i32> for NumberOrOperator {
     fn from(value: i32) -> Self {
         NumberOrOperator::Number(value)
     }
 }
 
-impl From<Operator> for NumberOrOperator {
+impl<N> From<Operator> for NumberOrOperator<N> {
     fn from(value: Operator) -> Self {
         NumberOrOperator::Operator(value)
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Operator {
     Sum,
     Sub,
@@ -117,19 +1024,255 @@ pub enum Operator {
     Div,
     LBra,
     RBra,
+    /// `n1` percent of `n2`, i.e. `n2 * n1 / 100`.
+    PercentOf,
+    /// Unary negation of the operand that follows.
+    Neg,
+    /// Greatest common divisor of the operands, which is never negative.
+    Gcd,
+    /// Least common multiple of the operands, which is never negative.
+    Lcm,
+    /// The first operand clamped between the second and the third one.
+    Clamp,
+    /// Unary sign of the operand that follows, `-1`, `0` or `1`.
+    Signum,
+    /// Sum of the decimal digits of the operand before it, which is never negative.
+    DigitSum,
+    /// Integer square root of the operand before it, rounded down.
+    Sqrt,
+    /// `n1` rounded to `n2` decimal places, half away from zero, so `3.14159t2` is `3.14`.
+    /// The number of places must be an integer from `0` to `15`, a negative one
+    /// does not round to tens but produces [`ParseError::InvalidPrecision`].
+    /// Only supported by [`parse_number`], where an integer is left as it is.
+    RoundTo,
+}
+
+impl Operator {
+    /// All the operators of the grammar.
+    pub const ALL: [Operator; 15] = [
+        Operator::Sum,
+        Operator::Sub,
+        Operator::Mul,
+        Operator::Div,
+        Operator::LBra,
+        Operator::RBra,
+        Operator::PercentOf,
+        Operator::Neg,
+        Operator::Gcd,
+        Operator::Lcm,
+        Operator::Clamp,
+        Operator::Signum,
+        Operator::DigitSum,
+        Operator::Sqrt,
+        Operator::RoundTo,
+    ];
+
+    /// The operator denoted by the given rule character, if any.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'a' => Some(Operator::Sum),
+            'b' => Some(Operator::Sub),
+            'c' => Some(Operator::Mul),
+            'd' => Some(Operator::Div),
+            'e' => Some(Operator::LBra),
+            'f' => Some(Operator::RBra),
+            'p' => Some(Operator::PercentOf),
+            'n' => Some(Operator::Neg),
+            'h' => Some(Operator::Gcd),
+            'l' => Some(Operator::Lcm),
+            'k' => Some(Operator::Clamp),
+            's' => Some(Operator::Signum),
+            'q' => Some(Operator::DigitSum),
+            'r' => Some(Operator::Sqrt),
+            't' => Some(Operator::RoundTo),
+            _ => None,
+        }
+    }
+
+    /// The rule character denoting the operator, the inverse of [`Operator::from_char`].
+    pub fn to_char(self) -> char {
+        match self {
+            Operator::Sum => 'a',
+            Operator::Sub => 'b',
+            Operator::Mul => 'c',
+            Operator::Div => 'd',
+            Operator::LBra => 'e',
+            Operator::RBra => 'f',
+            Operator::PercentOf => 'p',
+            Operator::Neg => 'n',
+            Operator::Gcd => 'h',
+            Operator::Lcm => 'l',
+            Operator::Clamp => 'k',
+            Operator::Signum => 's',
+            Operator::DigitSum => 'q',
+            Operator::Sqrt => 'r',
+            Operator::RoundTo => 't',
+        }
+    }
+
+    /// The conventional symbol of the operator, like `+` for [`Operator::Sum`].
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Operator::Sum => "+",
+            Operator::Sub | Operator::Neg => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::LBra => "(",
+            Operator::RBra => ")",
+            Operator::PercentOf => "% of",
+            Operator::Gcd => "gcd",
+            Operator::Lcm => "lcm",
+            Operator::Clamp => "clamp",
+            Operator::Signum => "sgn",
+            Operator::DigitSum => "digitsum",
+            Operator::Sqrt => "isqrt",
+            Operator::RoundTo => "round to",
+        }
+    }
+
+    /// The number of operands the operator is applied to, `0` for parentheses.
+    pub fn arity(self) -> usize {
+        match self {
+            Operator::LBra | Operator::RBra => 0,
+            Operator::Neg | Operator::Signum | Operator::DigitSum | Operator::Sqrt => 1,
+            Operator::Clamp => 3,
+            _ => 2,
+        }
+    }
+
+    /// Whether the operator is unary and applies to the operand before it.
+    pub fn is_postfix(self) -> bool {
+        matches!(self, Operator::DigitSum | Operator::Sqrt)
+    }
+
+    /// Applies a unary operator to its operand, checking for overflow.
+    /// The `span` of the operation is reported in case of error.
+    pub(crate) fn apply_unary(self, n: i32, span: Span) -> Result<i32, ParseError> {
+        let result = match self {
+            Operator::Neg => n.checked_neg(),
+            Operator::Signum => Some(n.signum()),
+            Operator::DigitSum => digit_sum(n.unsigned_abs().into()).try_into().ok(),
+            Operator::Sqrt if n < 0 => return Err(ParseError::NegativeSquareRoot(span)),
+            Operator::Sqrt => Some(n.isqrt()),
+            // An operator of another arity, in a tree or a queue built by hand.
+            _ => return Err(ParseError::DisallowedOperator(self, span)),
+        };
+        result.ok_or(ParseError::Overflow(span))
+    }
+
+    /// Applies a ternary operator to its operands, checking that the bounds are ordered.
+    /// The `span` of the operation is reported in case of error.
+    pub(crate) fn apply_ternary(
+        self,
+        n1: i32,
+        n2: i32,
+        n3: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        match self {
+            Operator::Clamp if n2 > n3 => Err(ParseError::InvalidBounds(span)),
+            Operator::Clamp => Ok(n1.clamp(n2, n3)),
+            _ => Err(ParseError::DisallowedOperator(self, span)),
+        }
+    }
+
+    /// Applies a binary operator to its operands, checking for overflow and division by zero.
+    /// The `span` of the operation is reported in case of error.
+    ///
+    /// A multiplication or a division of a non negative number by a power of two is a shift,
+    /// a fast path which gives the same results, overflows included.
+    pub(crate) fn apply(
+        self,
+        n1: i32,
+        n2: i32,
+        round_mode: RoundMode,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        let shift = (n1 >= 0 && n2 > 0 && n2.count_ones() == 1).then(|| n2.trailing_zeros());
+        let result = match (self, shift) {
+            // The shifted out bits must be zero, and the sign bit must stay zero.
+            (Operator::Mul, Some(shift)) => (n1 <= i32::MAX >> shift).then(|| n1 << shift),
+            // A non negative quotient rounded toward zero is a shift right.
+            (Operator::Div, Some(shift))
+                if matches!(round_mode, RoundMode::Truncate | RoundMode::Floor) =>
+            {
+                Some(n1 >> shift)
+            }
+            _ => self.apply_checked(n1, n2, round_mode, span)?,
+        };
+        result.ok_or(ParseError::Overflow(span))
+    }
+
+    /// The result of [`Operator::apply`] without the fast path, `None` on overflow.
+    fn apply_checked(
+        self,
+        n1: i32,
+        n2: i32,
+        round_mode: RoundMode,
+        span: Span,
+    ) -> Result<Option<i32>, ParseError> {
+        Ok(match self {
+            Operator::Sum => n1.checked_add(n2),
+            Operator::Sub => n1.checked_sub(n2),
+            Operator::Mul => n1.checked_mul(n2),
+            Operator::Div if n2 == 0 => return Err(ParseError::DivisionByZero(span)),
+            Operator::Div => round_mode
+                .divide(n1.into(), n2.into())
+                .and_then(|n| n.try_into().ok()),
+            // The product is computed in i64, so that only the final result can overflow.
+            Operator::PercentOf => round_mode
+                .divide(i64::from(n1) * i64::from(n2), 100)
+                .and_then(|n| n.try_into().ok()),
+            Operator::Gcd => gcd(n1.unsigned_abs().into(), n2.unsigned_abs().into())
+                .try_into()
+                .ok(),
+            Operator::Lcm => lcm(n1.unsigned_abs().into(), n2.unsigned_abs().into())
+                .and_then(|n| n.try_into().ok()),
+            Operator::RoundTo => return Err(ParseError::DisallowedOperator(self, span)),
+            _ => return Err(ParseError::DisallowedOperator(self, span)),
+        })
+    }
+}
+
+/// The conventional symbol of the operator, see [`Operator::symbol`].
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
 }
 
+/// The sum of the decimal digits of a number.
+pub(crate) fn digit_sum(mut n: u64) -> u64 {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
+
+/// The greatest common divisor with the Euclidean algorithm, where `gcd(0, n) = n`.
+pub(crate) fn gcd(mut n1: u64, mut n2: u64) -> u64 {
+    while n2 != 0 {
+        (n1, n2) = (n2, n1 % n2);
+    }
+    n1
+}
+
+/// The least common multiple, where `lcm(0, n) = 0`, returns `None` on overflow.
+pub(crate) fn lcm(n1: u64, n2: u64) -> Option<u64> {
+    if n1 == 0 || n2 == 0 {
+        return Some(0);
+    }
+    (n1 / gcd(n1, n2)).checked_mul(n2)
+}
+
+/// # Panics
+/// Panics if the character denotes no operator,
+/// see [`Operator::from_char`] for a non-panicking version.
 impl From<char> for Operator {
     fn from(value: char) -> Self {
-        match value {
-            'a' => Operator::Sum,
-            'b' => Operator::Sub,
-            'c' => Operator::Mul,
-            'd' => Operator::Div,
-            'e' => Operator::LBra,
-            'f' => Operator::RBra,
-            _ => panic!("Invalid character"),
-        }
+        Operator::from_char(value).expect("Invalid character")
     }
 }
 
@@ -160,3 +1303,1153 @@ fn invalid_character() {
 fn missing_right_parenthesis_test() {
     parse("123ae2d2");
 }
+
+#[test]
+fn operator_arity_tests() {
+    let span = Span::new(0, 1);
+    let disallowed = |o| Err(ParseError::DisallowedOperator(o, span));
+    for o in Operator::ALL {
+        if o.arity() != 1 {
+            assert_eq!(o.apply_unary(1, span), disallowed(o));
+        }
+        if o.arity() != 2 {
+            assert_eq!(o.apply(1, 2, RoundMode::default(), span), disallowed(o));
+        }
+        if o.arity() != 3 {
+            assert_eq!(o.apply_ternary(1, 0, 2, span), disallowed(o));
+        }
+    }
+}
+
+#[test]
+fn try_parse_errors() {
+    assert_eq!(try_parse("3a2c4"), Ok(20));
+    assert_eq!(
+        try_parse("abcdefg"),
+        Err(ParseError::InvalidCharacter('g', Span::new(6, 7)))
+    );
+    assert_eq!(
+        try_parse("3fa2"),
+        Err(ParseError::MissingLeftParenthesis(Span::new(1, 2)))
+    );
+    assert_eq!(
+        try_parse("123ae2d2"),
+        Err(ParseError::MissingRightParenthesis(Span::new(4, 5)))
+    );
+    assert_eq!(
+        try_parse("e1a2fce3a4fce5c6"),
+        Err(ParseError::MissingRightParenthesis(Span::new(12, 13)))
+    );
+    assert_eq!(
+        try_parse("e1a2fce3a4ffc5"),
+        Err(ParseError::MissingLeftParenthesis(Span::new(11, 12)))
+    );
+    assert_eq!(
+        try_parse("2147483648"),
+        Err(ParseError::NumberOverflow(Span::new(0, 10)))
+    );
+    assert_eq!(
+        try_parse("3a"),
+        Err(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+    assert_eq!(
+        try_parse("3d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        try_parse("1a65536c65536"),
+        Err(ParseError::Overflow(Span::new(0, 13)))
+    );
+    assert_eq!(
+        try_parse("1ae3d0f"),
+        Err(ParseError::DivisionByZero(Span::new(3, 6)))
+    );
+}
+
+#[test]
+fn semantically_equal_tests() {
+    assert_eq!(semantically_equal("3a2", "2a3"), Ok(true));
+    assert_eq!(semantically_equal("2c3", "6"), Ok(true));
+    assert_eq!(semantically_equal("3a2c4", "3ae2c4f"), Ok(false));
+    assert_eq!(
+        semantically_equal("3a2", "3d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn result_sign_tests() {
+    assert_eq!(result_sign("3b5"), Ok(Ordering::Less));
+    assert_eq!(result_sign("3a2c4"), Ok(Ordering::Greater));
+    assert_eq!(result_sign("5b5"), Ok(Ordering::Equal));
+    assert_eq!(result_sign(""), Ok(Ordering::Equal));
+    assert_eq!(result_sign("2147483647a1"), Ok(Ordering::Greater));
+    assert_eq!(result_sign("n3000000000"), Ok(Ordering::Less));
+    assert_eq!(
+        result_sign("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn allowed_operators_tests() {
+    let config = ParserConfig::without_division();
+    assert_eq!(try_parse_with("3a2c4", &config), Ok(20));
+    assert_eq!(
+        try_parse_with("32a2d2", &config),
+        Err(ParseError::DisallowedOperator(
+            Operator::Div,
+            Span::new(4, 5)
+        ))
+    );
+
+    let config = ParserConfig {
+        allowed_operators: [Operator::Sum, Operator::Sub].into_iter().collect(),
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("500a10b66", &config), Ok(444));
+    assert_eq!(
+        try_parse_with("3ae4f", &config),
+        Err(ParseError::DisallowedOperator(
+            Operator::LBra,
+            Span::new(2, 3)
+        ))
+    );
+}
+
+#[test]
+fn round_mode_tests() {
+    let with_mode = |round_mode| ParserConfig {
+        round_mode,
+        ..Default::default()
+    };
+    let cases = [
+        (RoundMode::Truncate, [3, -3, 1, 3]),
+        (RoundMode::Floor, [3, -4, 1, 3]),
+        (RoundMode::Ceil, [4, -3, 2, 3]),
+        (RoundMode::HalfUp, [4, -4, 1, 3]),
+        (RoundMode::HalfEven, [4, -4, 1, 3]),
+    ];
+    for (mode, expected) in cases {
+        let config = with_mode(mode);
+        let results = ["7d2", "0b7d2", "5d4", "6d2"].map(|s| try_parse_with(s, &config).unwrap());
+        assert_eq!(results, expected, "{mode:?}");
+    }
+    assert_eq!(
+        try_parse_with("5d2", &with_mode(RoundMode::HalfEven)),
+        Ok(2)
+    );
+    assert_eq!(
+        try_parse_with("0b5d2", &with_mode(RoundMode::HalfEven)),
+        Ok(-2)
+    );
+}
+
+#[test]
+fn percent_of_tests() {
+    assert_eq!(try_parse("15p200"), Ok(30));
+    assert_eq!(try_parse("10p200a5"), Ok(25));
+    assert_eq!(try_parse("50pe3a2f"), Ok(2));
+    assert_eq!(
+        try_parse("2147483647p200"),
+        Err(ParseError::Overflow(Span::new(0, 14)))
+    );
+    let config = ParserConfig {
+        round_mode: RoundMode::HalfUp,
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("50p5", &config), Ok(3));
+}
+
+#[test]
+fn neg_tests() {
+    assert_eq!(try_parse("n5"), Ok(-5));
+    assert_eq!(try_parse("3cn2"), Ok(-6));
+    assert_eq!(try_parse("n5a3"), Ok(-2));
+    assert_eq!(try_parse("3bn2"), Ok(5));
+    assert_eq!(try_parse("nn5"), Ok(5));
+    assert_eq!(try_parse("ne3a2fc2"), Ok(-10));
+    assert_eq!(
+        try_parse("na3"),
+        Err(ParseError::EmptyOperand(Span::new(0, 1)))
+    );
+    assert_eq!(
+        try_parse("n"),
+        Err(ParseError::EmptyOperand(Span::new(0, 1)))
+    );
+}
+
+#[test]
+fn pipeline_tests() {
+    use NumberOrOperator::{Number, Operator as Op};
+
+    let tokens = tokenize("3ae2c4f").unwrap();
+    assert_eq!(tokens.len(), 7);
+    let queue = to_rpn(&tokens).unwrap();
+    assert_eq!(
+        queue,
+        [
+            Number(3),
+            Number(2),
+            Number(4),
+            Op(Operator::Mul),
+            Op(Operator::Sum)
+        ]
+    );
+    assert_eq!(eval_rpn(&queue), Ok(11));
+
+    let tokens = tokenize("3a2f").unwrap();
+    assert_eq!(
+        to_rpn(&tokens),
+        Err(ParseError::MissingLeftParenthesis(Span::new(3, 4)))
+    );
+    let queue = [
+        Number(1),
+        Number(3),
+        Number(0),
+        Op(Operator::Div),
+        Op(Operator::Sum),
+    ];
+    assert_eq!(
+        eval_rpn(&queue),
+        Err(ParseError::DivisionByZero(Span::new(1, 3)))
+    );
+    assert_eq!(
+        eval_rpn(&[Number(1), Number(2)]),
+        Err(ParseError::Malformed(Span::new(1, 2)))
+    );
+    assert_eq!(
+        eval_rpn(&[Number(1), Number(2)]),
+        eval_prefix(&[Number(1), Number(2)])
+    );
+    assert_eq!(
+        eval_rpn(&[Number(1), Number(2), Op(Operator::LBra)]),
+        Err(ParseError::Malformed(Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn rpn_display_tests() {
+    let queue = to_rpn(&tokenize("3a2").unwrap()).unwrap();
+    assert_eq!(
+        queue
+            .iter()
+            .map(|item| item.to_string())
+            .collect::<Vec<_>>(),
+        ["3", "2", "+"]
+    );
+    let queue = to_rpn(&tokenize("3ae2cn4fp200").unwrap()).unwrap();
+    assert_eq!(rpn_to_string(&queue), "3 2 4 - * + 200 % of");
+    assert_eq!(rpn_to_string::<i32>(&[]), "");
+    assert_eq!(Operator::Gcd.to_string(), "gcd");
+}
+
+#[test]
+fn power_of_two_tests() {
+    // The fast path must agree with the checked arithmetic on every edge.
+    let operands = [0, 1, 3, 7, 1 << 30, i32::MAX, -1, -7, i32::MIN];
+    let powers = (0..31).map(|shift| 1 << shift);
+    for n2 in powers.chain([-1, -2, 3]) {
+        for n1 in operands {
+            for round_mode in [RoundMode::Truncate, RoundMode::Floor, RoundMode::Ceil] {
+                for op in [Operator::Mul, Operator::Div] {
+                    let span = Span::default();
+                    assert_eq!(
+                        op.apply(n1, n2, round_mode, span),
+                        op.apply_checked(n1, n2, round_mode, span)
+                            .and_then(|n| n.ok_or(ParseError::Overflow(span))),
+                        "{n1} {op} {n2}"
+                    );
+                }
+            }
+        }
+    }
+    assert_eq!(try_parse("1c8d4c2d4"), Ok(1));
+    assert_eq!(
+        try_parse("1073741824c2"),
+        Err(ParseError::Overflow(Span::new(0, 12)))
+    );
+}
+
+#[test]
+fn empty_parentheses_tests() {
+    for (string, span) in [
+        ("ef", (0, 2)),
+        ("3aef", (2, 4)),
+        ("efc2", (0, 2)),
+        ("eeff", (1, 3)),
+    ] {
+        let span = Span::new(span.0, span.1);
+        assert_eq!(try_parse(string), Err(ParseError::EmptyParentheses(span)));
+        assert_eq!(
+            parse_fixed::<4>(string),
+            Err(ParseError::EmptyParentheses(span))
+        );
+    }
+
+    let config = ParserConfig {
+        allow_empty_parentheses: true,
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("ef", &config), Ok(0));
+    assert_eq!(try_parse_with("3aef", &config), Ok(3));
+    assert_eq!(try_parse_with("efc2", &config), Ok(0));
+    assert_eq!(try_parse_with("eefa1f", &config), Ok(1));
+    assert_eq!(
+        try_parse("efg"),
+        Err(ParseError::InvalidCharacter('g', Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn constants_tests() {
+    let config = ParserConfig {
+        constants: std::collections::HashMap::from([
+            ("MAX".to_owned(), 100),
+            ("K_2".to_owned(), 3),
+            ("BIG".to_owned(), 1 << 40),
+        ]),
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("MAXd4", &config), Ok(25));
+    assert_eq!(try_parse_with("K_2ceMAXa1f", &config), Ok(303));
+    assert_eq!(
+        try_parse_with("1aFOO", &config),
+        Err(ParseError::UnknownConstant(
+            "FOO".to_owned(),
+            Span::new(2, 5)
+        ))
+    );
+    assert_eq!(
+        try_parse_with("BIGa1", &config),
+        Err(ParseError::NumberOverflow(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_number_with("BIGc2", &config),
+        Ok(Number::Int(1 << 41))
+    );
+    assert_eq!(
+        try_parse("MAX"),
+        Err(ParseError::UnknownConstant(
+            "MAX".to_owned(),
+            Span::new(0, 3)
+        ))
+    );
+}
+
+#[test]
+fn regression_corpus() {
+    let corpus = include_str!("../tests/corpus.txt");
+    for line in corpus.lines().filter(|line| !line.starts_with('#')) {
+        let (string, expected) = line.split_once(" => ").expect("malformed corpus line");
+        let result = try_parse(string);
+        let actual = match &result {
+            Ok(n) => n.to_string(),
+            Err(err) => format!("error: {err}"),
+        };
+        assert_eq!(actual, expected, "{string}");
+        assert_eq!(
+            parse_ast(string).and_then(|expr| expr.eval()),
+            result,
+            "{string}"
+        );
+    }
+}
+
+#[test]
+fn algebraic_identity_tests() {
+    // A xorshift generator, so that the expressions are the same on every run.
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+    // A valid expression, with balanced parentheses and small numbers.
+    fn expression(state: &mut u64, depth: u32) -> String {
+        match next(state) % 6 {
+            _ if depth == 0 => (next(state) % 100).to_string(),
+            0 => (next(state) % 100).to_string(),
+            1 => format!("n{}", expression(state, depth - 1)),
+            2 => format!("e{}f", expression(state, depth - 1)),
+            _ => {
+                let op = ['a', 'b', 'c', 'd'][next(state) as usize % 4];
+                let (x, y) = (expression(state, depth - 1), expression(state, depth - 1));
+                format!("{x}{op}{y}")
+            }
+        }
+    }
+
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    let mut checked = 0;
+    while checked < 1000 {
+        let x = expression(&mut state, 5);
+        // Overflows and divisions by zero are not interesting here.
+        let Ok(n) = try_parse(&x) else { continue };
+        checked += 1;
+        assert_eq!(try_parse(&format!("{x}a0")), Ok(n), "{x}a0");
+        assert_eq!(try_parse(&format!("{x}b0")), Ok(n), "{x}b0");
+        assert_eq!(try_parse(&format!("{x}c1")), Ok(n), "{x}c1");
+        assert_eq!(try_parse(&format!("{x}d1")), Ok(n), "{x}d1");
+        assert_eq!(try_parse(&format!("e{x}f")), Ok(n), "e{x}f");
+        assert_eq!(try_parse(&format!("0a{x}")), Ok(n), "0a{x}");
+        assert_eq!(parse_ast(&x).and_then(|expr| expr.eval()), Ok(n), "{x}");
+    }
+}
+
+#[test]
+fn strict_tests() {
+    let config = ParserConfig {
+        strict: true,
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("3ae2c4f", &config), Ok(11));
+    assert_eq!(
+        try_parse_with("3a2;extra", &config),
+        Err(ParseError::TrailingInput { position: 3 })
+    );
+    assert_eq!(
+        try_parse_with("3a2e4f", &config),
+        Err(ParseError::TrailingInput { position: 3 })
+    );
+    assert_eq!(
+        try_parse_with("e1fn2", &config),
+        Err(ParseError::TrailingInput { position: 3 })
+    );
+    assert_eq!(
+        try_parse_with("3a2g", &config).unwrap_err().to_string(),
+        "Unexpected input after the expression at 3"
+    );
+    assert_eq!(
+        try_parse_with("3ag", &config),
+        Err(ParseError::InvalidCharacter('g', Span::new(2, 3)))
+    );
+    assert_eq!(
+        try_parse_with("3a2f", &config),
+        Err(ParseError::MissingLeftParenthesis(Span::new(3, 4)))
+    );
+    assert_eq!(
+        try_parse("3a2e4f"),
+        Err(ParseError::Malformed(Span::new(2, 5)))
+    );
+}
+
+#[test]
+fn parse_span_tests() {
+    assert_eq!(parse_span("1a2c3", 2..5), Ok(6));
+    assert_eq!(parse_span("1a2c3", 0..5), Ok(9));
+    assert_eq!(parse_span("3ce1a2f", 2..7), Ok(3));
+    assert_eq!(
+        parse_span("3ce1a2f", 3..7),
+        Err(ParseError::MissingLeftParenthesis(Span::new(6, 7)))
+    );
+    assert_eq!(
+        parse_span("123a4", 1..5),
+        Err(ParseError::InvalidSelection(Span::new(1, 5)))
+    );
+    assert_eq!(
+        parse_span("1a2", 2..4),
+        Err(ParseError::InvalidSelection(Span::new(2, 4)))
+    );
+    assert_eq!(
+        parse_span("1a2d0", 2..5),
+        Err(ParseError::DivisionByZero(Span::new(2, 5)))
+    );
+}
+
+#[test]
+fn consecutive_operators_tests() {
+    let consecutive = |position| Err(ParseError::ConsecutiveOperators { position });
+    assert_eq!(try_parse("3aa2"), consecutive(2));
+    assert_eq!(try_parse("3ac2e1d2f"), consecutive(2));
+    assert_eq!(parse_fixed::<4>("3aa2"), consecutive(2));
+    assert_eq!(try_parse("3an2"), Ok(1));
+    assert_eq!(try_parse("3aen2f"), Ok(1));
+    assert_eq!(
+        try_parse("3aa2g"),
+        Err(ParseError::InvalidCharacter('g', Span::new(4, 5)))
+    );
+    assert_eq!(
+        render_error("3aa2", &try_parse("3aa2").unwrap_err()),
+        "error: Invalid expression, consecutive operators at 2\n3aa2\n  ^"
+    );
+}
+
+#[test]
+fn check_operands_tests() {
+    assert_eq!(
+        try_parse("1e2f"),
+        Err(ParseError::Malformed(Span::new(2, 3)))
+    );
+    assert_eq!(
+        try_parse("e1fe2fa3"),
+        Err(ParseError::Malformed(Span::new(4, 8)))
+    );
+    // The missing operand is found before the division by zero is evaluated.
+    assert_eq!(
+        try_parse("1d0a"),
+        Err(ParseError::EmptyOperand(Span::new(3, 4)))
+    );
+    assert_eq!(parse_fixed::<4>("3a2e4f"), try_parse("3a2e4f"));
+    assert_eq!(try_parse("5k0k10"), Ok(5));
+    assert_eq!(try_parse("n3q"), Ok(-3));
+}
+
+#[test]
+fn parse_prefix_tests() {
+    assert_eq!(parse_prefix("3a2;extra"), Ok((5, 3)));
+    assert_eq!(parse_prefix("3ae2c4f1a1"), Ok((11, 7)));
+    assert_eq!(parse_prefix("3a2"), Ok((5, 3)));
+    assert_eq!(
+        parse_prefix("1d0é"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_prefix("3a;"),
+        Err(ParseError::InvalidCharacter(';', Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn override_tests() {
+    let config = ParserConfig::default().with_override(Operator::Div, |n1, n2, _| {
+        Ok(n1.checked_div(n2).unwrap_or(0))
+    });
+    assert_eq!(try_parse_with("3a4d0", &config), Ok(0));
+    assert_eq!(try_parse_with("8d2a1c3", &config), Ok(15));
+    assert_eq!(parse_ast("7d0").unwrap().eval_with(&config), Ok(0));
+    assert_eq!(
+        try_parse_with("2147483647a1d0", &config),
+        Err(ParseError::Overflow(Span::new(0, 12)))
+    );
+
+    let config = config.with_override(Operator::Sum, |_, _, span| {
+        Err(ParseError::DisallowedOperator(Operator::Sum, span))
+    });
+    assert_eq!(
+        try_parse_with("1b2a3", &config),
+        Err(ParseError::DisallowedOperator(
+            Operator::Sum,
+            Span::new(0, 5)
+        ))
+    );
+    assert_eq!(config.clone(), config);
+    assert_ne!(config, ParserConfig::default());
+}
+
+#[test]
+fn wide_accumulator_tests() {
+    let config = ParserConfig {
+        wide_accumulator: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        try_parse_with("1000000c1000000d1000000", &config),
+        Ok(1000000)
+    );
+    assert_eq!(
+        try_parse("1000000c1000000d1000000"),
+        Err(ParseError::Overflow(Span::new(0, 15)))
+    );
+    assert_eq!(try_parse_with("2147483647a1b1", &config), Ok(i32::MAX));
+    assert_eq!(try_parse_with("3000000000d2", &config), Ok(1500000000));
+    assert_eq!(
+        try_parse_with("2147483647a1", &config),
+        Err(ParseError::ResultOverflow(Span::new(0, 12)))
+    );
+    assert_eq!(
+        try_parse_with("3037000500c3037000500", &config),
+        Err(ParseError::Overflow(Span::new(0, 21)))
+    );
+    assert_eq!(try_parse_with("", &config), Ok(0));
+}
+
+#[test]
+fn parse_wide_tests() {
+    assert_eq!(parse_wide("2147483647c2"), Ok(4294967294));
+    assert_eq!(parse_wide("1000000c1000000d1000000"), Ok(1000000));
+    assert_eq!(parse_wide("3a2c4"), Ok(20));
+    assert_eq!(
+        parse_wide("3000000000d2"),
+        Err(ParseError::NumberOverflow(Span::new(0, 10)))
+    );
+    assert_eq!(
+        parse_wide("2147483647c2147483647c4"),
+        Err(ParseError::Overflow(Span::new(0, 23)))
+    );
+}
+
+#[test]
+fn max_literal_tests() {
+    let config = ParserConfig {
+        max_literal: Some(1_000_000),
+        constants: std::collections::HashMap::from([("BIG".to_owned(), 5_000_000)]),
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("1000000c1000", &config), Ok(1_000_000_000));
+    let err = try_parse_with("3a1000001b2", &config).unwrap_err();
+    assert_eq!(
+        err,
+        ParseError::LiteralTooLarge {
+            value: 1_000_001,
+            span: Span::new(2, 9)
+        }
+    );
+    assert_eq!(err.to_string(), "Number literal 1000001 too large at 2..9");
+    assert_eq!(try_parse_with("BIG", &config), Ok(5_000_000));
+    assert_eq!(
+        parse_number_with("2000000.5a2000000", &config),
+        Err(ParseError::LiteralTooLarge {
+            value: 2_000_000,
+            span: Span::new(10, 17)
+        })
+    );
+    // The span is the one of the source text, whose width differs from the value's.
+    let config = ParserConfig {
+        max_literal: Some(5),
+        number_format: NumberFormat {
+            base_suffixes: true,
+            ..NumberFormat::PLAIN
+        },
+        ..Default::default()
+    };
+    let err = try_parse_with("7FFFFFFFH", &config).unwrap_err();
+    assert_eq!(err.span(), Some(Span::new(0, 9)));
+    assert_eq!(
+        render_error("7FFFFFFFH", &err),
+        "error: Number literal 2147483647 too large at 0..9\n7FFFFFFFH\n^^^^^^^^^"
+    );
+}
+
+#[test]
+fn max_intermediate_tests() {
+    let config = ParserConfig {
+        max_intermediate: Some(1000),
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("10c100d100", &config), Ok(10));
+    assert_eq!(
+        try_parse_with("100c100d100", &config),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 7)))
+    );
+    assert_eq!(try_parse_with("5000", &config), Ok(5000));
+    assert_eq!(
+        try_parse_with("n5000", &config),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 5)))
+    );
+    assert_eq!(
+        parse_ast("100c100d100").unwrap().eval_with(&config),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 7)))
+    );
+    let wide = ParserConfig {
+        wide_accumulator: true,
+        ..config
+    };
+    assert_eq!(
+        try_parse_with("1000000c1000000d1000000", &wide),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 15)))
+    );
+    assert_eq!(
+        parse_number_with("999.5a1", &wide),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 7)))
+    );
+}
+
+#[test]
+fn operator_caps_tests() {
+    let config = ParserConfig {
+        operator_caps: [(Operator::Sum, 1000), (Operator::Mul, 10000)].into(),
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("900a200", &config), Ok(1000));
+    assert_eq!(try_parse_with("900a200c20", &config), Ok(10000));
+    assert_eq!(try_parse_with("n900b900b200", &config), Ok(-2000));
+    assert_eq!(try_parse_with("n900an900", &config), Ok(-1000));
+    let config = ParserConfig {
+        max_intermediate: Some(500),
+        ..config
+    };
+    assert_eq!(
+        try_parse_with("900a200", &config),
+        Err(ParseError::IntermediateTooLarge(Span::new(0, 7)))
+    );
+}
+
+#[test]
+fn gcd_lcm_tests() {
+    assert_eq!(try_parse("12h18"), Ok(6));
+    assert_eq!(try_parse("4l6"), Ok(12));
+    assert_eq!(try_parse("12h8a1"), Ok(5));
+    assert_eq!(try_parse("0h7"), Ok(7));
+    assert_eq!(try_parse("0h0"), Ok(0));
+    assert_eq!(try_parse("0l7"), Ok(0));
+    assert_eq!(try_parse("n12hn18"), Ok(6));
+    assert_eq!(try_parse("n4l6"), Ok(12));
+    assert_eq!(
+        try_parse("65536l65537"),
+        Err(ParseError::Overflow(Span::new(0, 11)))
+    );
+    assert_eq!(
+        try_parse("0b2147483647b1h0"),
+        Err(ParseError::Overflow(Span::new(0, 16)))
+    );
+    assert_eq!(parse_number("5000000000h15"), Ok(Number::Int(5)));
+    assert_eq!(
+        parse_number("1.5h3"),
+        Err(ParseError::DisallowedOperator(
+            Operator::Gcd,
+            Span::new(0, 5)
+        ))
+    );
+}
+
+#[test]
+fn parenthesizations_equal_tests() {
+    assert_eq!(parenthesizations_equal("e1a2fa3", "1ae2a3f"), Ok(true));
+    assert_eq!(parenthesizations_equal("e1b2fb3", "1be2b3f"), Ok(false));
+    assert_eq!(parenthesizations_equal("2c3a4", "e2c3fa4"), Ok(true));
+    assert_eq!(parenthesizations_equal("1a2", "2a1"), Ok(false));
+    assert_eq!(
+        parenthesizations_equal("1a2", "1de2b2f"),
+        Err(ParseError::DivisionByZero(Span::new(0, 6)))
+    );
+}
+
+#[test]
+fn parse_compose_tests() {
+    assert_eq!(parse_compose("?c2", '?', "3a4"), Ok(14));
+    assert_eq!(parse_compose("e?b1fc?", '?', "2c2"), Ok(12));
+    assert_eq!(parse_compose("5", '?', "1"), Ok(5));
+    assert_eq!(parse_compose("éaé", 'é', "n1"), Ok(-2));
+    assert_eq!(
+        parse_compose("1d?", '?', "2b2"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_compose("?a1g", '?', "1"),
+        Err(ParseError::InvalidCharacter('g', Span::new(3, 4)))
+    );
+    assert_eq!(
+        parse_compose("?", '?', "1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn all_parenthesized_results_tests() {
+    assert_eq!(
+        all_parenthesized_results("1b2b3"),
+        Ok(BTreeSet::from([-4, 2]))
+    );
+    assert_eq!(
+        all_parenthesized_results("1a2c3b4"),
+        Ok(BTreeSet::from([-3, -1, 3, 5]))
+    );
+    assert_eq!(all_parenthesized_results("4d0c1"), Ok(BTreeSet::new()));
+    assert_eq!(all_parenthesized_results("7"), Ok(BTreeSet::from([7])));
+    assert_eq!(all_parenthesized_results(""), Ok(BTreeSet::from([0])));
+    assert_eq!(
+        all_parenthesized_results("e1a2fc3"),
+        Err(ParseError::DisallowedOperator(
+            Operator::LBra,
+            Span::new(0, 1)
+        ))
+    );
+    assert_eq!(
+        all_parenthesized_results(&format!("1{}", "a1".repeat(12))),
+        Err(ParseError::CapacityExceeded)
+    );
+}
+
+#[test]
+fn parse_lenient_tests() {
+    assert_eq!(parse_lenient("3ae2"), Ok(5));
+    assert_eq!(parse_lenient("3ce2aee1a1"), Ok(12));
+    assert_eq!(parse_lenient("f3a2ff"), Ok(5));
+    assert_eq!(parse_lenient("3ae2f"), Ok(5));
+    assert_eq!(
+        parse_lenient("3ae"),
+        Err(ParseError::EmptyParentheses(Span::new(2, 3)))
+    );
+    assert_eq!(parse_lenient("3ae2a"), try_parse("3ae2af"));
+}
+
+#[test]
+fn parse_flat_tests() {
+    assert_eq!(parse_flat("3ce4a2f"), Ok(14));
+    assert_eq!(try_parse("3ce4a2f"), Ok(18));
+    assert_eq!(parse_flat("3ae2c4f"), Ok(20));
+    assert_eq!(parse_flat("ee1a2fc3"), Ok(9));
+    assert_eq!(parse_flat("ef"), Ok(0));
+    assert_eq!(parse_flat("6de3b3f"), Ok(-1));
+    assert_eq!(
+        parse_flat("6d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn running_values_tests() {
+    assert_eq!(running_values("10a5b3"), Ok(vec![10, 15, 12]));
+    assert_eq!(running_values("2ce3a4f"), Ok(vec![2, 7, 14]));
+    assert_eq!(running_values("n5a1"), Ok(vec![5, -5, -4]));
+    assert_eq!(running_values(""), Ok(vec![]));
+    assert_eq!(
+        running_values("1a2d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 5)))
+    );
+}
+
+#[test]
+fn parse_product_shortcircuit_tests() {
+    assert_eq!(parse_product_shortcircuit("5c0c7c9"), Ok(0));
+    assert_eq!(parse_product_shortcircuit("0c2147483647c2"), Ok(0));
+    assert_eq!(parse_product_shortcircuit("e1d0fc0"), Ok(0));
+    assert_eq!(parse_product_shortcircuit("3c4c5"), Ok(60));
+    assert_eq!(parse_product_shortcircuit("0c5a3"), Ok(3));
+    assert_eq!(parse_product_shortcircuit("5c10"), Ok(50));
+    assert_eq!(
+        parse_product_shortcircuit("e1d0fc2"),
+        Err(ParseError::DivisionByZero(Span::new(1, 4)))
+    );
+    assert_eq!(
+        parse_product_shortcircuit("5c0c"),
+        Err(ParseError::EmptyOperand(Span::new(3, 4)))
+    );
+    assert_eq!(
+        parse_product_shortcircuit("5c0ce0"),
+        Err(ParseError::MissingRightParenthesis(Span::new(4, 5)))
+    );
+    assert_eq!(
+        parse_product_shortcircuit("0c0g"),
+        Err(ParseError::InvalidCharacter('g', Span::new(3, 4)))
+    );
+}
+
+#[test]
+fn parse_roman_tests() {
+    assert_eq!(parse_roman("1990a4"), Ok("MCMXCIV".to_owned()));
+    assert_eq!(parse_roman("1"), Ok("I".to_owned()));
+    assert_eq!(parse_roman("3999"), Ok("MMMCMXCIX".to_owned()));
+    assert_eq!(parse_roman("7c7"), Ok("XLIX".to_owned()));
+    assert_eq!(parse_roman("0"), Err(ParseError::NotRoman(0)));
+    assert_eq!(parse_roman("4000"), Err(ParseError::NotRoman(4000)));
+    assert_eq!(parse_roman("n5"), Err(ParseError::NotRoman(-5)));
+    assert_eq!(
+        parse_roman("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn parse_formatted_tests() {
+    assert_eq!(
+        parse_formatted("500a10b66c32", ','),
+        Ok("14,208".to_owned())
+    );
+    assert_eq!(parse_formatted("999", ','), Ok("999".to_owned()));
+    assert_eq!(
+        parse_formatted("1000c1000", '.'),
+        Ok("1.000.000".to_owned())
+    );
+    assert_eq!(parse_formatted("n123456", '_'), Ok("-123_456".to_owned()));
+    assert_eq!(
+        parse_formatted("0b2147483647b1", ','),
+        Ok("-2,147,483,648".to_owned())
+    );
+    assert_eq!(parse_formatted("", ','), Ok("0".to_owned()));
+    assert_eq!(
+        parse_formatted("1d0", ','),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn top_level_terms_tests() {
+    assert_eq!(
+        top_level_terms("1ae2b3fb4c5"),
+        Ok(vec!["1".to_owned(), "e2b3f".to_owned(), "4c5".to_owned()])
+    );
+    assert_eq!(top_level_terms("n2c3"), Ok(vec!["n2c3".to_owned()]));
+    assert_eq!(top_level_terms(""), Ok(vec![]));
+    assert_eq!(
+        top_level_terms("1ae2"),
+        Err(ParseError::MissingRightParenthesis(Span::new(2, 3)))
+    );
+    assert_eq!(
+        top_level_terms("1fa2"),
+        Err(ParseError::MissingLeftParenthesis(Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn collapse_parens_tests() {
+    assert_eq!(collapse_parens("eee3fff").as_deref(), Ok("3"));
+    assert_eq!(collapse_parens("eee3fffae1a2f").as_deref(), Ok("3ae1a2f"));
+    assert_eq!(collapse_parens("eee1a2fffc4").as_deref(), Ok("e1a2fc4"));
+    assert_eq!(collapse_parens("ne5fae16fr").as_deref(), Ok("n5a16r"));
+    assert_eq!(collapse_parens("e1a2fa3").as_deref(), Ok("e1a2fa3"));
+    assert_eq!(collapse_parens("").as_deref(), Ok(""));
+    assert_eq!(
+        collapse_parens("3e4f"),
+        Err(ParseError::Malformed(Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn extract_expressions_tests() {
+    assert_eq!(
+        extract_expressions("result: 3a2, then 4c5"),
+        [Ok(5), Ok(20)]
+    );
+    assert_eq!(extract_expressions("nothing to see here"), []);
+    assert_eq!(extract_expressions("e3a2fc2 and 7"), [Ok(10), Ok(7)]);
+    assert_eq!(
+        extract_expressions("x = 1d0; y = 2"),
+        [Err(ParseError::DivisionByZero(Span::new(4, 7))), Ok(2)]
+    );
+}
+
+#[test]
+fn clamp_tests() {
+    assert_eq!(try_parse("5k0k10"), Ok(5));
+    assert_eq!(try_parse("15k0k10"), Ok(10));
+    assert_eq!(try_parse("n5k0k10"), Ok(0));
+    assert_eq!(try_parse("5k0a1k10"), Ok(5));
+    assert_eq!(try_parse("5k6a1k10"), Ok(7));
+    assert_eq!(try_parse("2a3k0k4a1"), Ok(5));
+    assert_eq!(try_parse("5k0k10k6k8"), Ok(6));
+    assert_eq!(try_parse("5ke3k0k1fk10"), Ok(5));
+    assert_eq!(try_parse("5k0ke3k4k5f"), Ok(4));
+    assert_eq!(
+        try_parse("5k10k0"),
+        Err(ParseError::InvalidBounds(Span::new(0, 6)))
+    );
+    assert_eq!(
+        try_parse("5k0"),
+        Err(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+    assert_eq!(
+        try_parse("e5k0fk1"),
+        Err(ParseError::EmptyOperand(Span::new(2, 3)))
+    );
+    assert_eq!(
+        parse_steps("15k0k10").map(|(_, steps)| steps),
+        Ok(vec!["clamp(15, 0, 10) = 10".to_owned()])
+    );
+}
+
+#[test]
+fn div_by_zero_tests() {
+    let with = |div_by_zero| ParserConfig {
+        div_by_zero,
+        ..Default::default()
+    };
+    assert_eq!(try_parse_with("3ae4d0f", &with(DivByZero::Zero)), Ok(3));
+    assert_eq!(try_parse_with("1dn0", &with(DivByZero::Max)), Ok(i32::MAX));
+    assert_eq!(try_parse_with("n1d0", &with(DivByZero::Max)), Ok(i32::MAX));
+    assert_eq!(
+        try_parse_with("5d0b1", &with(DivByZero::Custom(-1))),
+        Ok(-2)
+    );
+    assert_eq!(try_parse_with("7d2", &with(DivByZero::Zero)), Ok(3));
+    assert_eq!(
+        try_parse_with("5d0", &with(DivByZero::Error)),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_number_with("1.5d0.0a1", &with(DivByZero::Custom(2))),
+        Ok(Number::Float(3.0))
+    );
+    let wide = ParserConfig {
+        wide_accumulator: true,
+        ..with(DivByZero::Zero)
+    };
+    assert_eq!(try_parse_with("5000000000d0a1", &wide), Ok(1));
+}
+
+#[test]
+fn merge_tests() {
+    let base = ParserConfig {
+        strict: true,
+        constants: std::collections::HashMap::from([("A".to_owned(), 1), ("B".to_owned(), 2)]),
+        ..ParserConfig::without_division()
+    };
+    let config = base.merge(&ParserConfig {
+        constants: std::collections::HashMap::from([("B".to_owned(), 3)]),
+        max_intermediate: Some(100),
+        ..Default::default()
+    });
+    assert!(config.strict);
+    assert!(!config.allowed_operators.contains(&Operator::Div));
+    assert_eq!(config.max_intermediate, Some(100));
+    assert_eq!(try_parse_with("AaB", &config), Ok(4));
+    assert_eq!(base.merge(&ParserConfig::default()), base);
+    assert_eq!(ParserConfig::default().merge(&base), base);
+}
+
+#[test]
+fn stack_snapshots_tests() {
+    let config = ParserConfig {
+        stack_snapshots: true,
+        ..Default::default()
+    };
+    let err = try_parse_with("1a2c3ae4d0f", &config).unwrap_err();
+    assert_eq!(err.stack_snapshot(), Some(&[9, 4, 0][..]));
+    assert_eq!(err.span(), Some(Span::new(7, 10)));
+    assert_eq!(
+        err.to_string(),
+        "Division by zero at 7..10, with stack [9, 4, 0]"
+    );
+    assert_eq!(
+        try_parse_with("2147483647a1", &config)
+            .unwrap_err()
+            .stack_snapshot(),
+        Some(&[i32::MAX, 1][..])
+    );
+    assert_eq!(try_parse_with("1a2c3", &config), Ok(9));
+    assert_eq!(try_parse("1d0").unwrap_err().stack_snapshot(), None);
+}
+
+#[test]
+fn precedence_tests() {
+    let config = ParserConfig::default().with_conventional_precedence();
+    assert_eq!(try_parse_with("3a2c4", &config), Ok(11));
+    assert_eq!(try_parse_with("32a2d2", &config), Ok(33));
+    assert_eq!(try_parse_with("500a10b66c32", &config), Ok(-1602));
+    assert_eq!(try_parse_with("3ae4c66fb32", &config), Ok(235));
+    assert_eq!(try_parse_with("3c4d2aee2a4c41fc4f", &config), Ok(670));
+    assert_eq!(try_parse_with("n2c3a1", &config), Ok(-5));
+    assert_eq!(try_parse_with("5k0k10a1", &config), Ok(5));
+    assert_eq!(try_parse_with("1b2b3", &config), Ok(-4));
+
+    let mut config = config;
+    config
+        .precedence
+        .insert(Operator::Sub, (1, Associativity::Right));
+    assert_eq!(try_parse_with("1b2b3", &config), Ok(2));
+    config
+        .precedence
+        .insert(Operator::Sum, (5, Associativity::Left));
+    assert_eq!(try_parse_with("2c3a4", &config), Ok(14));
+}
+
+#[test]
+fn parse_with_precedence_tests() {
+    let config = ParserConfig::default().with_conventional_precedence();
+    for string in [
+        "3a2c4",
+        "32a2d2",
+        "500a10b66c32",
+        "1b2b3",
+        "7",
+        "2147483647a1a1d0",
+        "1a2147483647c2",
+        "1a4d0b2147483647c2",
+        "2c3a4c5b6d2",
+        "3ae4c66fb32",
+        "3a",
+        "3aa2",
+        "n2c3a1",
+        "65536c65536a",
+        "7a0d0 bb",
+    ] {
+        assert_eq!(
+            parse_with_precedence(string),
+            try_parse_with(string, &config),
+            "{string}"
+        );
+    }
+    assert_eq!(parse_with_precedence("2c3a4c5b6d2"), Ok(23));
+    assert_eq!(
+        parse_with_precedence("65536c65536a"),
+        Err(ParseError::EmptyOperand(Span::new(11, 12)))
+    );
+    assert!(contains_parens("3ae4f"));
+    assert!(!contains_parens("3a4"));
+}
+
+#[test]
+fn signum_tests() {
+    assert_eq!(try_parse("s5"), Ok(1));
+    assert_eq!(try_parse("sn5"), Ok(-1));
+    assert_eq!(try_parse("s0"), Ok(0));
+    assert_eq!(try_parse("2csn5"), Ok(-2));
+    assert_eq!(try_parse("se3b7fc10"), Ok(-10));
+    assert_eq!(try_parse("s3b7"), Ok(-6));
+    assert_eq!(
+        parse_steps("sn2").map(|(_, steps)| steps),
+        Ok(vec!["-(2) = -2".to_owned(), "sgn(-2) = -1".to_owned()])
+    );
+}
+
+#[test]
+fn sqrt_tests() {
+    assert_eq!(try_parse("16r"), Ok(4));
+    assert_eq!(try_parse("17r"), Ok(4));
+    assert_eq!(try_parse("15r"), Ok(3));
+    assert_eq!(try_parse("2147483647r"), Ok(46340));
+    assert_eq!(try_parse("e9a7frc2"), Ok(8));
+    assert_eq!(try_parse("n16r"), Ok(-4));
+    assert_eq!(parse_fixed::<4>("1a16rc2"), Ok(10));
+    assert_eq!(parse_number("99r"), Ok(Number::Int(9)));
+    assert_eq!(
+        try_parse("e0b16fr"),
+        Err(ParseError::NegativeSquareRoot(Span::new(1, 7)))
+    );
+    assert_eq!(
+        parse_number("2.0r"),
+        Err(ParseError::DisallowedOperator(
+            Operator::Sqrt,
+            Span::new(0, 4)
+        ))
+    );
+}
+
+#[test]
+fn digit_sum_tests() {
+    assert_eq!(try_parse("123q"), Ok(6));
+    assert_eq!(try_parse("123qa1"), Ok(7));
+    assert_eq!(try_parse("1a123q"), Ok(7));
+    assert_eq!(try_parse("e99a1fq"), Ok(1));
+    assert_eq!(try_parse("n19q"), Ok(-10));
+    assert_eq!(try_parse("e0bn19fq"), Ok(10));
+    assert_eq!(try_parse("2147483647qq"), Ok(10));
+    assert_eq!(parse_fixed::<4>("1a123qc2"), Ok(14));
+    assert_eq!(
+        parse_steps("45q").map(|(_, steps)| steps),
+        Ok(vec!["digitsum(45) = 9".to_owned()])
+    );
+    let strict = ParserConfig {
+        strict: true,
+        ..Default::default()
+    };
+    assert_eq!(parse_prefix("12qa1;extra"), Ok((4, 5)));
+    assert_eq!(try_parse_with("12q", &strict), Ok(3));
+    assert_eq!(
+        try_parse("q1"),
+        Err(ParseError::EmptyOperand(Span::new(0, 1)))
+    );
+}
+
+#[test]
+fn min_negation_tests() {
+    // `i32::MIN` has no positive counterpart, so negating it overflows.
+    assert_eq!(try_parse("n2147483647b1"), Ok(i32::MIN));
+    assert_eq!(
+        try_parse("nen2147483647b1f"),
+        Err(ParseError::Overflow(Span::new(0, 15)))
+    );
+    assert_eq!(
+        try_parse("n2147483647b2"),
+        Err(ParseError::Overflow(Span::new(0, 13)))
+    );
+    assert_eq!(
+        try_parse("n2147483648"),
+        Err(ParseError::NumberOverflow(Span::new(1, 11)))
+    );
+    assert_eq!(
+        parse_fixed::<4>("nen2147483647b1f"),
+        Err(ParseError::Overflow(Span::new(0, 15)))
+    );
+}
@@ -0,0 +1,210 @@
+use crate::Operator;
+
+/// Tells whether an expression is well formed, at compile time if needed:
+/// every operand is a number literal that fits in an `i32` or a parenthesized expression,
+/// optionally preceded by `n` or `s` and followed by `q` or `r`,
+/// and operands are separated by binary operators, or by the two `k` of a clamp,
+/// whose lower bound must be parenthesized to contain a clamp.
+/// The empty expression is well formed too, as it evaluates to `0`.
+///
+/// This is stricter than [`try_parse`](crate::try_parse), which for instance accepts `3e4fa`,
+/// and it cannot tell whether the evaluation overflows or divides by zero.
+pub const fn is_well_formed(string: &str) -> bool {
+    matches!(well_formed_prefix(string.as_bytes()), Some(true))
+}
+
+/// Tells whether some expression that [`is_well_formed`] starts with the given bytes,
+/// and if so whether they are already a whole well formed expression.
+const fn well_formed_prefix(bytes: &[u8]) -> Option<bool> {
+    let mut i = 0;
+    let mut depth = 0usize;
+    let mut after_operand = false;
+    let mut literal = 0u64;
+    // The bit of every paren depth with the lower bound of a clamp not yet closed.
+    let mut clamps = 0u64;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'0'..=b'9' => {
+                if !after_operand {
+                    literal = 0;
+                } else if !bytes[i - 1].is_ascii_digit() {
+                    return None;
+                }
+                literal = literal * 10 + (bytes[i] - b'0') as u64;
+                if literal > i32::MAX as u64 {
+                    return None;
+                }
+                after_operand = true;
+            }
+            b'e' | b'n' | b's' if !after_operand => {
+                if bytes[i] == b'e' {
+                    depth += 1;
+                }
+            }
+            b'q' | b'r' if after_operand => {}
+            b'f' if after_operand && depth > 0 && clamps & (1 << depth) == 0 => depth -= 1,
+            b'k' if after_operand && depth < 64 => {
+                clamps ^= 1 << depth;
+                after_operand = false;
+            }
+            b'a' | b'b' | b'c' | b'd' | b'p' | b'h' | b'l' if after_operand => {
+                after_operand = false
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some(depth == 0 && clamps == 0 && (after_operand || bytes.is_empty()))
+}
+
+/// All the expressions that are [well formed](is_well_formed) with at most `max_len` characters,
+/// from the shortest, and in alphabetical order for the same length,
+/// where digits come before the rule characters: `""`, `"0"`, ... `"9"`, `"00"`, ...
+///
+/// The number of expressions grows exponentially with the length,
+/// but they are generated lazily, visiting only the prefixes of well formed expressions.
+pub fn enumerate_expressions(max_len: usize) -> impl Iterator<Item = String> {
+    let mut alphabet = ('0'..='9')
+        .chain(Operator::ALL.map(Operator::to_char))
+        .collect::<Vec<_>>();
+    alphabet.sort_unstable();
+    (0..=max_len).flat_map(move |len| Expressions {
+        alphabet: alphabet.clone(),
+        stack: vec![String::new()],
+        len,
+    })
+}
+
+/// Depth first search of the well formed expressions of length `len`.
+struct Expressions {
+    alphabet: Vec<char>,
+    stack: Vec<String>,
+    len: usize,
+}
+
+impl Iterator for Expressions {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some(prefix) = self.stack.pop() {
+            if prefix.len() == self.len {
+                if is_well_formed(&prefix) {
+                    return Some(prefix);
+                }
+                continue;
+            }
+            // Pushed in reverse, so that they are visited in alphabetical order.
+            for &c in self.alphabet.iter().rev() {
+                let mut extended = prefix.clone();
+                extended.push(c);
+                if well_formed_prefix(extended.as_bytes()).is_some() {
+                    self.stack.push(extended);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Evaluates an expression with [`parse`](crate::parse),
+/// checking at compile time that it [`is_well_formed`](crate::is_well_formed).
+///
+/// ```
+/// use arithmetic_parser::arith;
+///
+/// assert_eq!(arith!("3a2c4"), 20);
+/// ```
+///
+/// A malformed expression does not compile:
+///
+/// ```compile_fail
+/// use arithmetic_parser::arith;
+///
+/// arith!("abcdefg");
+/// ```
+#[macro_export]
+macro_rules! arith {
+    ($expression:literal) => {{
+        const _: () = ::core::assert!(
+            $crate::is_well_formed($expression),
+            ::core::concat!("malformed expression: ", $expression)
+        );
+        $crate::parse($expression)
+    }};
+}
+
+/// Same as [`arith!`], but evaluates the expression with [`try_parse`](crate::try_parse),
+/// since a well formed expression can still fail with a division by zero or an overflow.
+///
+/// ```
+/// use arithmetic_parser::{try_arith, ParseError, Span};
+///
+/// assert_eq!(try_arith!("3d0"), Err(ParseError::DivisionByZero(Span::new(0, 3))));
+/// ```
+#[macro_export]
+macro_rules! try_arith {
+    ($expression:literal) => {{
+        const _: () = ::core::assert!(
+            $crate::is_well_formed($expression),
+            ::core::concat!("malformed expression: ", $expression)
+        );
+        $crate::try_parse($expression)
+    }};
+}
+
+#[test]
+fn is_well_formed_tests() {
+    for string in [
+        "",
+        "3a2c4",
+        "3c4d2aee2a4c41fc4f",
+        "nne3bn2f",
+        "2147483647",
+        "12h18l4",
+        "5k0k10",
+        "5ke1k2k3fk4",
+    ] {
+        assert!(is_well_formed(string), "{string}");
+    }
+    for string in [
+        "abcdefg",
+        "3a",
+        "a3",
+        "e3",
+        "3f",
+        "ef",
+        "e3f4",
+        "3n2",
+        "3e4fa",
+        "5k0",
+        "e5k0fk1",
+        "2147483648",
+        "3 a 2",
+    ] {
+        assert!(!is_well_formed(string), "{string}");
+    }
+    assert_eq!(arith!("500a10b66c32"), 14208);
+    assert_eq!(
+        try_arith!("1a2147483647"),
+        Err(crate::ParseError::Overflow(crate::Span::new(0, 12)))
+    );
+}
+
+#[test]
+fn enumerate_expressions_tests() {
+    let expressions = enumerate_expressions(2).collect::<Vec<_>>();
+    assert_eq!(expressions.len(), 1 + 10 + 100 + 20 + 20);
+    assert_eq!(expressions[..3], ["", "0", "1"]);
+    assert_eq!(expressions[11..13], ["00", "01"]);
+    assert_eq!(expressions.last().map(String::as_str), Some("s9"));
+
+    let expressions = enumerate_expressions(4).collect::<Vec<_>>();
+    assert!(expressions.contains(&"e1f".to_owned()));
+    assert!(!expressions.contains(&"3a".to_owned()));
+    for expression in &expressions {
+        assert!(matches!(
+            crate::try_parse(expression),
+            Ok(_) | Err(crate::ParseError::DivisionByZero(_))
+        ));
+    }
+}
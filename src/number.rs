@@ -0,0 +1,388 @@
+use std::fmt;
+
+use crate::{
+    digit_sum, gcd, lcm, spanned_rpn,
+    token::{digits_len, Literal},
+    NumberFormat, NumberOrOperator, Operator, ParseError, ParserConfig, RoundMode, Span,
+};
+
+/// A number that is either an integer or a floating point one.
+/// Integers are promoted to floating point as soon as they meet one.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// The value of the number as a floating point one.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+
+    fn apply(
+        op: Operator,
+        n1: Number,
+        n2: Number,
+        round_mode: RoundMode,
+        span: Span,
+    ) -> Result<Number, ParseError> {
+        if let (Number::Int(n1), Number::Int(n2)) = (n1, n2) {
+            let result = match op {
+                Operator::Sum => n1.checked_add(n2),
+                Operator::Sub => n1.checked_sub(n2),
+                Operator::Mul => n1.checked_mul(n2),
+                Operator::Div if n2 == 0 => return Err(ParseError::DivisionByZero(span)),
+                Operator::Div => round_mode.divide(n1, n2),
+                Operator::PercentOf => n1.checked_mul(n2).and_then(|n| round_mode.divide(n, 100)),
+                Operator::Gcd => gcd(n1.unsigned_abs(), n2.unsigned_abs()).try_into().ok(),
+                Operator::Lcm => {
+                    lcm(n1.unsigned_abs(), n2.unsigned_abs()).and_then(|n| n.try_into().ok())
+                }
+                // An integer has no decimal places to round.
+                Operator::RoundTo => {
+                    precision(Number::Int(n2), span)?;
+                    Some(n1)
+                }
+                _ => unreachable!("{op:?} is not a binary operator"),
+            };
+            return result.map(Number::Int).ok_or(ParseError::Overflow(span));
+        }
+
+        if op == Operator::RoundTo {
+            let scale = 10_f64.powi(precision(n2, span)?);
+            return float((n1.as_f64() * scale).round() / scale, span);
+        }
+        let (n1, n2) = (n1.as_f64(), n2.as_f64());
+        let result = match op {
+            Operator::Sum => n1 + n2,
+            Operator::Sub => n1 - n2,
+            Operator::Mul => n1 * n2,
+            Operator::Div if n2 == 0.0 => return Err(ParseError::DivisionByZero(span)),
+            Operator::Div => n1 / n2,
+            Operator::PercentOf => n1 * n2 / 100.0,
+            Operator::Gcd | Operator::Lcm => return Err(ParseError::DisallowedOperator(op, span)),
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        float(result, span)
+    }
+
+    fn apply_unary(op: Operator, n: Number, span: Span) -> Result<Number, ParseError> {
+        match (op, n) {
+            (Operator::Neg, Number::Int(n)) => n
+                .checked_neg()
+                .map(Number::Int)
+                .ok_or(ParseError::Overflow(span)),
+            (Operator::Neg, Number::Float(n)) => Ok(Number::Float(-n)),
+            (Operator::Signum, Number::Int(n)) => Ok(Number::Int(n.signum())),
+            // Unlike `f64::signum`, zero has no sign.
+            (Operator::Signum, Number::Float(n)) if n != 0.0 => Ok(Number::Float(n.signum())),
+            (Operator::Signum, Number::Float(_)) => Ok(Number::Float(0.0)),
+            (Operator::DigitSum, Number::Int(n)) => {
+                Ok(Number::Int(digit_sum(n.unsigned_abs()) as i64))
+            }
+            (Operator::DigitSum | Operator::Sqrt, Number::Float(_)) => {
+                Err(ParseError::DisallowedOperator(op, span))
+            }
+            (Operator::Sqrt, Number::Int(n)) if n < 0 => Err(ParseError::NegativeSquareRoot(span)),
+            (Operator::Sqrt, Number::Int(n)) => Ok(Number::Int(n.isqrt())),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        }
+    }
+
+    fn apply_ternary(
+        op: Operator,
+        n1: Number,
+        n2: Number,
+        n3: Number,
+        span: Span,
+    ) -> Result<Number, ParseError> {
+        if op != Operator::Clamp {
+            unreachable!("{op:?} is not a ternary operator");
+        }
+        // Integer bounds are compared exactly, since an `f64` cannot tell large ones apart.
+        match (n1, n2, n3) {
+            (Number::Int(_), Number::Int(n2), Number::Int(n3)) if n2 > n3 => {
+                Err(ParseError::InvalidBounds(span))
+            }
+            (Number::Int(n1), Number::Int(n2), Number::Int(n3)) => {
+                Ok(Number::Int(n1.clamp(n2, n3)))
+            }
+            _ if n2.as_f64() > n3.as_f64() => Err(ParseError::InvalidBounds(span)),
+            _ => Ok(Number::Float(n1.as_f64().clamp(n2.as_f64(), n3.as_f64()))),
+        }
+    }
+}
+
+/// The number of decimal places to round to, an integer from `0` to `15`,
+/// since an `f64` has no more significant digits.
+fn precision(n: Number, span: Span) -> Result<i32, ParseError> {
+    match n {
+        Number::Int(n @ 0..=15) => Ok(n as i32),
+        _ => Err(ParseError::InvalidPrecision(span)),
+    }
+}
+
+/// A floating point result, which overflows if it is no longer finite.
+fn float(n: f64, span: Span) -> Result<Number, ParseError> {
+    if n.is_finite() {
+        Ok(Number::Float(n))
+    } else {
+        Err(ParseError::Overflow(span))
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Int(n) => write!(f, "{n}"),
+            Number::Float(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+/// A run of digits, optionally followed by a `.` and the digits of the fractional part.
+impl Literal for Number {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        let len = format.integer_len(rest);
+        match rest[len..].strip_prefix(format.decimal) {
+            Some(fraction) => len + format.decimal.len_utf8() + digits_len(fraction),
+            None => len,
+        }
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (literal, radix) = format.radix(literal);
+        if literal.contains('.') {
+            literal.parse().ok().map(Number::Float)
+        } else {
+            i64::from_str_radix(&literal, radix).ok().map(Number::Int)
+        }
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        Some(Number::Int(value))
+    }
+
+    fn integer_value(self) -> Option<i64> {
+        match self {
+            Number::Int(n) => Some(n),
+            Number::Float(_) => None,
+        }
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but number literals can also have
+/// a fractional part, like `2.5`. Integers are `i64` and are promoted to `f64`
+/// when an operator is applied to an integer and a floating point number,
+/// so `3a2.5` is `Float(5.5)`, while `7d2` is still an integer division giving `Int(3)`.
+/// A result can be rounded inline with [`Operator::RoundTo`], like `1d3.0t2` for `Float(0.33)`.
+pub fn parse_number(string: &str) -> Result<Number, ParseError> {
+    parse_number_with(string, &ParserConfig::default())
+}
+
+/// Same as [`parse_number`], but the grammar is customized by the given [`ParserConfig`].
+pub fn parse_number_with(string: &str, config: &ParserConfig) -> Result<Number, ParseError> {
+    let queue = spanned_rpn(string, config, None)?;
+    eval_numbers(queue, config).map(|(n, _)| n)
+}
+
+/// The value of the expression `a` as a percentage of the expression `b`, both evaluated
+/// like with [`parse_number`], so `1d3.0` of `2` is about `16.67`:
+/// a zero `b` produces [`ParseError::DivisionByZero`], with the span of `b`.
+pub fn parse_ratio_percent(a: &str, b: &str) -> Result<f64, ParseError> {
+    parse_ratio_percent_with(a, b, &ParserConfig::default())
+}
+
+/// Same as [`parse_ratio_percent`], but the grammar is customized by the given [`ParserConfig`],
+/// and a zero `b` gives the [`div_by_zero`](ParserConfig::div_by_zero) result as it is,
+/// not as a percentage.
+pub fn parse_ratio_percent_with(
+    a: &str,
+    b: &str,
+    config: &ParserConfig,
+) -> Result<f64, ParseError> {
+    let n1 = parse_number_with(a, config)?.as_f64();
+    let (n2, span) = eval_numbers(spanned_rpn(b, config, None)?, config)?;
+    if n2.as_f64() == 0.0 {
+        return config
+            .div_by_zero
+            .value()
+            .map(f64::from)
+            .ok_or(ParseError::DivisionByZero(span));
+    }
+    Ok(n1 / n2.as_f64() * 100.0)
+}
+
+/// Evaluates an expression in Reverse Polish Notation (RPN), where every item has a span,
+/// returning the result with its span.
+/// Divisions are rounded and results are bounded as configured.
+pub(crate) fn eval_numbers(
+    queue: impl IntoIterator<Item = (NumberOrOperator<Number>, Span)>,
+    config: &ParserConfig,
+) -> Result<(Number, Span), ParseError> {
+    let bounded = |n: Number, span| {
+        let too_large = match (n, config.max_intermediate) {
+            (_, None) => false,
+            (Number::Int(n), Some(max)) => i128::from(n).abs() > i128::from(max),
+            (Number::Float(n), Some(max)) => n.abs() > max as f64,
+        };
+        if too_large {
+            Err(ParseError::IntermediateTooLarge(span))
+        } else {
+            Ok(n)
+        }
+    };
+    let mut output_stack = Vec::<(Number, Span)>::new();
+    for (token, span) in queue {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(o) if o.arity() == 1 => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = span.to(n_span);
+                output_stack.push((bounded(Number::apply_unary(o, n, span)?, span)?, span));
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 3 => {
+                let (n3, n3_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n2, _) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n3_span);
+                let result = Number::apply_ternary(o, n1, n2, n3, span)?;
+                output_stack.push((bounded(result, span)?, span));
+            }
+            NumberOrOperator::Operator(o) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                let by_zero = o == Operator::Div && n2.as_f64() == 0.0;
+                let result = match config.div_by_zero.value() {
+                    Some(n) if by_zero => match (n1, n2) {
+                        (Number::Int(_), Number::Int(_)) => Number::Int(n.into()),
+                        _ => Number::Float(n.into()),
+                    },
+                    _ => Number::apply(o, n1, n2, config.round_mode, span)?,
+                };
+                output_stack.push((bounded(result, span)?, span));
+            }
+        }
+    }
+
+    Ok(output_stack
+        .pop()
+        .unwrap_or((Number::Int(0), Span::default())))
+}
+
+#[test]
+fn parse_number_tests() {
+    assert_eq!(parse_number("3a2c4"), Ok(Number::Int(20)));
+    assert_eq!(parse_number("3a2.5"), Ok(Number::Float(5.5)));
+    assert_eq!(parse_number("7d2"), Ok(Number::Int(3)));
+    assert_eq!(parse_number("7.0d2"), Ok(Number::Float(3.5)));
+    assert_eq!(parse_number("n1.5c2"), Ok(Number::Float(-3.0)));
+    assert_eq!(parse_number("3."), Ok(Number::Float(3.0)));
+    assert_eq!(parse_number("sn0.5"), Ok(Number::Float(-1.0)));
+    assert_eq!(parse_number("s0.0"), Ok(Number::Float(0.0)));
+    assert_eq!(parse_number("5000000000c2"), Ok(Number::Int(10000000000)));
+    assert_eq!(parse_number(""), Ok(Number::Int(0)));
+    assert_eq!(
+        parse_number("1.5d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 5)))
+    );
+    assert_eq!(
+        parse_number("1.2.3"),
+        Err(ParseError::InvalidCharacter('.', Span::new(3, 4)))
+    );
+    assert_eq!(Number::Float(2.5).to_string(), "2.5");
+    assert_eq!(
+        parse_number("1k9007199254740993k9007199254740992"),
+        Err(ParseError::InvalidBounds(Span::new(0, 35)))
+    );
+    assert_eq!(
+        parse_number("9007199254740993k0k9007199254740993"),
+        Ok(Number::Int(9007199254740993))
+    );
+}
+
+#[test]
+fn number_format_tests() {
+    let european = ParserConfig {
+        number_format: NumberFormat::EUROPEAN,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_number_with("1.000,5a2", &european),
+        Ok(Number::Float(1002.5))
+    );
+    assert_eq!(
+        parse_number_with("1.234.567c2", &european),
+        Ok(Number::Int(2469134))
+    );
+    assert_eq!(
+        parse_number_with("1.00", &european),
+        Err(ParseError::InvalidCharacter('.', Span::new(1, 2)))
+    );
+
+    let english = ParserConfig {
+        number_format: NumberFormat::ENGLISH,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_number_with("1,000.5a2", &english),
+        Ok(Number::Float(1002.5))
+    );
+    assert_eq!(crate::try_parse_with("14,208d2", &english), Ok(7104));
+    assert_eq!(
+        crate::try_parse_with("1000,000", &english),
+        Err(ParseError::InvalidCharacter(',', Span::new(4, 5)))
+    );
+}
+
+#[test]
+fn round_to_tests() {
+    assert_eq!(parse_number("1.23456t2"), Ok(Number::Float(1.23)));
+    assert_eq!(parse_number("2.5t0"), Ok(Number::Float(3.0)));
+    assert_eq!(parse_number("n2.5t0"), Ok(Number::Float(-3.0)));
+    assert_eq!(parse_number("1d3.0t3"), Ok(Number::Float(0.333)));
+    assert_eq!(parse_number("1234t2"), Ok(Number::Int(1234)));
+    assert_eq!(
+        parse_number("3.14tn1"),
+        Err(ParseError::InvalidPrecision(Span::new(0, 7)))
+    );
+    assert_eq!(
+        parse_number("3.14t1.5"),
+        Err(ParseError::InvalidPrecision(Span::new(0, 8)))
+    );
+    assert_eq!(
+        parse_number("1t16"),
+        Err(ParseError::InvalidPrecision(Span::new(0, 4)))
+    );
+    assert_eq!(
+        crate::try_parse("3t2"),
+        Err(ParseError::DisallowedOperator(
+            Operator::RoundTo,
+            Span::new(0, 3)
+        ))
+    );
+}
+
+#[test]
+fn parse_ratio_percent_tests() {
+    assert_eq!(parse_ratio_percent("1a2", "12"), Ok(25.0));
+    assert_eq!(parse_ratio_percent("3", "1.5c2"), Ok(100.0));
+    assert_eq!(
+        parse_ratio_percent("1", "2b2"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    let config = ParserConfig {
+        div_by_zero: crate::DivByZero::Zero,
+        ..Default::default()
+    };
+    assert_eq!(parse_ratio_percent_with("1", "2b2", &config), Ok(0.0));
+    assert_eq!(
+        parse_ratio_percent("1g", "2"),
+        Err(ParseError::InvalidCharacter('g', Span::new(1, 2)))
+    );
+}
@@ -0,0 +1,101 @@
+use crate::{
+    eval_spanned_rpn, shunting_yard,
+    token::{Token, Tokens},
+    ParseError, ParserConfig, Span,
+};
+use std::ops::Range;
+
+/// A source of random numbers for [`parse_with_rng`], implemented by closures
+/// returning uniformly distributed `u64`, like `|| rng.next_u64()` with the `rand` crate.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<F: FnMut() -> u64> Rng for F {
+    fn next_u64(&mut self) -> u64 {
+        self()
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every `?` is an operand drawn from `range`
+/// with the given generator, from left to right, so the result is reproducible
+/// with a seeded generator. A `?` is an operand on its own, even next to digits,
+/// and drawing from an empty range produces [`ParseError::EmptyRange`].
+///
+/// ```
+/// use arithmetic_parser::parse_with_rng;
+///
+/// let mut rng = || u64::MAX / 2;
+/// assert_eq!(parse_with_rng("?c2a1", &mut rng, 0..10), Ok(9));
+/// ```
+pub fn parse_with_rng(
+    string: &str,
+    rng: &mut impl Rng,
+    range: Range<i32>,
+) -> Result<i32, ParseError> {
+    let config = ParserConfig::default();
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for (i, piece) in string.split('?').enumerate() {
+        if i > 0 {
+            let span = Span::new(offset - 1, offset);
+            tokens.push(
+                draw(rng, &range)
+                    .map(|n| (Token::Number(n), span))
+                    .ok_or(ParseError::EmptyRange(span)),
+            );
+        }
+        tokens.extend(Tokens::new(piece).map(|token| match token {
+            Ok((token, span)) => Ok((token, Span::new(span.start + offset, span.end + offset))),
+            Err(err) => Err(err.shifted(offset)),
+        }));
+        offset += piece.len() + 1;
+    }
+    eval_spanned_rpn(shunting_yard(tokens, &config, None)?, &config)
+}
+
+/// A number of the range, scaling the next random number to its width
+/// with a widening multiplication, returns `None` if the range is empty.
+fn draw(rng: &mut impl Rng, range: &Range<i32>) -> Option<i32> {
+    let width = u128::try_from(i64::from(range.end) - i64::from(range.start)).ok()?;
+    if width == 0 {
+        return None;
+    }
+    let n = (u128::from(rng.next_u64()) * width) >> 64;
+    // `n` is less than the width, so the number is in the range.
+    Some((i64::from(range.start) + n as i64) as i32)
+}
+
+#[test]
+fn parse_with_rng_tests() {
+    let mut values = [0, u64::MAX, 1 << 63].into_iter().cycle();
+    let mut rng = || values.next().unwrap();
+    assert_eq!(parse_with_rng("?a?c?", &mut rng, 1..7), Ok(28));
+    assert_eq!(parse_with_rng("3c2", &mut rng, 0..0), Ok(6));
+    assert_eq!(
+        parse_with_rng("1a?", &mut rng, 5..5),
+        Err(ParseError::EmptyRange(Span::new(2, 3)))
+    );
+    assert_eq!(
+        parse_with_rng("?d0", &mut rng, i32::MIN..i32::MAX),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_with_rng("1?", &mut rng, 0..10),
+        Err(ParseError::Malformed(Span::new(1, 2)))
+    );
+
+    // A linear congruential generator, seeded.
+    let mut state = 42u64;
+    let mut lcg = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        state
+    };
+    let results = (0..100)
+        .map(|_| parse_with_rng("?", &mut lcg, -3..3).unwrap())
+        .collect::<Vec<_>>();
+    assert!(results.iter().all(|n| (-3..3).contains(n)));
+    assert!((-3..3).all(|n| results.contains(&n)));
+}
@@ -0,0 +1,232 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    digit_sum, gcd, lcm, spanned_rpn, token::Literal, NumberFormat, NumberOrOperator, Operator,
+    ParseError, ParserConfig, Span,
+};
+
+/// A fraction in lowest terms, whose denominator is always positive.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    /// The fraction `numerator / denominator` reduced to lowest terms,
+    /// returns `None` if the denominator is zero or the result overflows.
+    pub fn new(numerator: i64, denominator: i64) -> Option<Self> {
+        Self::reduced(numerator.into(), denominator.into())
+    }
+
+    fn integer(n: i64) -> Self {
+        Self {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    /// Reduces a fraction computed in i128, where the product of two `i64` cannot overflow.
+    fn reduced(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = i128::try_from(gcd128(numerator.unsigned_abs(), denominator.unsigned_abs()))
+            .ok()?
+            .max(1)
+            * denominator.signum();
+        Some(Self {
+            numerator: (numerator / divisor).try_into().ok()?,
+            denominator: (denominator / divisor).try_into().ok()?,
+        })
+    }
+
+    pub fn numerator(self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> i64 {
+        self.denominator
+    }
+
+    pub(crate) fn apply(
+        op: Operator,
+        n1: Rational,
+        n2: Rational,
+        span: Span,
+    ) -> Result<Rational, ParseError> {
+        let (a, b) = (i128::from(n1.numerator), i128::from(n1.denominator));
+        let (c, d) = (i128::from(n2.numerator), i128::from(n2.denominator));
+        let result = match op {
+            Operator::Sum => Rational::reduced(a * d + c * b, b * d),
+            Operator::Sub => Rational::reduced(a * d - c * b, b * d),
+            Operator::Mul => Rational::reduced(a * c, b * d),
+            Operator::Div if c == 0 => return Err(ParseError::DivisionByZero(span)),
+            Operator::Div => Rational::reduced(a * d, b * c),
+            // The product of the denominators scaled by `100` may not fit in an i128.
+            Operator::PercentOf => (b * d)
+                .checked_mul(100)
+                .and_then(|denominator| Rational::reduced(a * c, denominator)),
+            Operator::Gcd | Operator::Lcm if b != 1 || d != 1 => {
+                return Err(ParseError::DisallowedOperator(op, span))
+            }
+            Operator::RoundTo => return Err(ParseError::DisallowedOperator(op, span)),
+            Operator::Gcd => gcd(n1.numerator.unsigned_abs(), n2.numerator.unsigned_abs())
+                .try_into()
+                .ok()
+                .map(Rational::integer),
+            Operator::Lcm => lcm(n1.numerator.unsigned_abs(), n2.numerator.unsigned_abs())
+                .and_then(|n| n.try_into().ok())
+                .map(Rational::integer),
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        result.ok_or(ParseError::Overflow(span))
+    }
+
+    fn apply_unary(op: Operator, n: Rational, span: Span) -> Result<Rational, ParseError> {
+        match op {
+            Operator::Neg => Ok(Rational {
+                numerator: n
+                    .numerator
+                    .checked_neg()
+                    .ok_or(ParseError::Overflow(span))?,
+                ..n
+            }),
+            Operator::Signum => Ok(Rational::integer(n.numerator.signum())),
+            Operator::DigitSum | Operator::Sqrt if n.denominator != 1 => {
+                Err(ParseError::DisallowedOperator(op, span))
+            }
+            Operator::DigitSum => digit_sum(n.numerator.unsigned_abs())
+                .try_into()
+                .map(Rational::integer)
+                .map_err(|_| ParseError::Overflow(span)),
+            Operator::Sqrt if n.numerator < 0 => Err(ParseError::NegativeSquareRoot(span)),
+            Operator::Sqrt => Ok(Rational::integer(n.numerator.isqrt())),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        }
+    }
+
+    fn apply_ternary(
+        op: Operator,
+        n1: Rational,
+        n2: Rational,
+        n3: Rational,
+        span: Span,
+    ) -> Result<Rational, ParseError> {
+        match op {
+            Operator::Clamp if n2 > n3 => Err(ParseError::InvalidBounds(span)),
+            Operator::Clamp => Ok(n1.clamp(n2, n3)),
+            _ => unreachable!("{op:?} is not a ternary operator"),
+        }
+    }
+}
+
+/// The greatest common divisor of two i128 magnitudes.
+fn gcd128(mut n1: u128, mut n2: u128) -> u128 {
+    while n2 != 0 {
+        (n1, n2) = (n2, n1 % n2);
+    }
+    n1
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The denominators are positive, so cross multiplying keeps the order.
+        let lhs = i128::from(self.numerator) * i128::from(other.denominator);
+        let rhs = i128::from(other.numerator) * i128::from(self.denominator);
+        lhs.cmp(&rhs)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `3/2`, or just `3` for an integer.
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            denominator => write!(f, "{}/{denominator}", self.numerator),
+        }
+    }
+}
+
+impl Literal for Rational {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        i64::literal_len(rest, format)
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        i64::parse_literal(literal, format).map(Rational::integer)
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        Some(Rational::integer(value))
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every division is exact,
+/// so the result is a [`Rational`] in lowest terms: `6d4` is `3/2`, and `1dn2` is `-1/2`.
+/// Numerators and denominators are `i64`.
+pub fn parse_rational(string: &str) -> Result<Rational, ParseError> {
+    let mut output_stack = Vec::<(Rational, Span)>::new();
+    for (token, span) in spanned_rpn(string, &ParserConfig::default(), None)? {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(o) if o.arity() == 1 => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = span.to(n_span);
+                output_stack.push((Rational::apply_unary(o, n, span)?, span));
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 3 => {
+                let (n3, n3_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n2, _) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n3_span);
+                output_stack.push((Rational::apply_ternary(o, n1, n2, n3, span)?, span));
+            }
+            NumberOrOperator::Operator(o) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                output_stack.push((Rational::apply(o, n1, n2, span)?, span));
+            }
+        }
+    }
+
+    Ok(output_stack.pop().map_or(Rational::integer(0), |(n, _)| n))
+}
+
+#[test]
+fn parse_rational_tests() {
+    let rational = |string| parse_rational(string).map(|n| n.to_string());
+    assert_eq!(rational("6d4"), Ok("3/2".to_owned()));
+    assert_eq!(
+        rational("1d9223372036854775807pe1d9223372036854775807f"),
+        Err(ParseError::Overflow(Span::new(0, 44)))
+    );
+    assert_eq!(rational("1dn2"), Ok("-1/2".to_owned()));
+    assert_eq!(rational("n1dn2"), Ok("1/2".to_owned()));
+    assert_eq!(rational("1d3a1d6"), Ok("2/9".to_owned()));
+    assert_eq!(rational("1d3ae1d6f"), Ok("1/2".to_owned()));
+    assert_eq!(rational("e1d3ae1d6ffc4"), Ok("2".to_owned()));
+    assert_eq!(rational("0dn5"), Ok("0".to_owned()));
+    assert_eq!(rational("50p1d3"), Ok("1/6".to_owned()));
+    assert_eq!(rational("7k1d2k5d2"), Ok("5/2".to_owned()));
+    assert_eq!(parse_rational("6d4"), Ok(Rational::new(-6, -4).unwrap()));
+    assert_eq!(Rational::new(1, 0), None);
+    assert_eq!(
+        parse_rational("1de2b2f"),
+        Err(ParseError::DivisionByZero(Span::new(0, 6)))
+    );
+    assert_eq!(
+        parse_rational("1d2h3"),
+        Err(ParseError::DisallowedOperator(
+            Operator::Gcd,
+            Span::new(0, 5)
+        ))
+    );
+}
@@ -0,0 +1,60 @@
+use crate::{eval_spanned_rpn, spanned_rpn, ParseError, ParserConfig};
+
+/// A stateful evaluator for interactive sessions,
+/// where the `$` token stands for the result of the last successful evaluation.
+///
+/// ```
+/// let mut repl = arithmetic_parser::Repl::new();
+/// assert_eq!(repl.eval("3a2"), Ok(5));
+/// assert_eq!(repl.eval("$c2"), Ok(10));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Repl {
+    config: ParserConfig,
+    last: Option<i32>,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a session whose expressions are customized by the given [`ParserConfig`].
+    pub fn with_config(config: ParserConfig) -> Self {
+        Self { config, last: None }
+    }
+
+    /// The result of the last successful evaluation, if any.
+    pub fn last(&self) -> Option<i32> {
+        self.last
+    }
+
+    /// Evaluates a line, replacing every `$` with the previous result.
+    /// A failed evaluation leaves the previous result untouched.
+    pub fn eval(&mut self, line: &str) -> Result<i32, ParseError> {
+        let queue = spanned_rpn(line, &self.config, self.last)?;
+        let result = eval_spanned_rpn(queue, &self.config)?;
+        self.last = Some(result);
+        Ok(result)
+    }
+}
+
+#[test]
+fn repl_tests() {
+    use crate::Span;
+
+    let mut repl = Repl::new();
+    assert_eq!(
+        repl.eval("$a1"),
+        Err(ParseError::NoPreviousResult(Span::new(0, 1)))
+    );
+    assert_eq!(repl.eval("3a2"), Ok(5));
+    assert_eq!(repl.eval("$c2"), Ok(10));
+    assert_eq!(
+        repl.eval("$d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(repl.last(), Some(10));
+    assert_eq!(repl.eval("0b$"), Ok(-10));
+    assert_eq!(repl.eval("e$a4fc$"), Ok(60));
+}
@@ -0,0 +1,643 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+};
+
+use crate::{
+    digit_sum, eval_spanned_rpn, gcd, lcm, spanned_rpn, Operator, ParseError, ParserConfig,
+    RoundMode, Span,
+};
+
+/// The semantics of the operators, to evaluate expressions with custom arithmetic,
+/// e.g. modular or saturating, see [`parse_with_semantics`].
+///
+/// The `span` of the operation is the one to report in case of error.
+pub trait OpSemantics {
+    /// Applies a binary operator to its operands.
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError>;
+
+    /// Applies a unary operator to its operand.
+    /// Defaults to the built-in semantics of the operator, checked for overflow,
+    /// like the negation, the signum, the digit sum or the square root.
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        op.apply_unary(a, span)
+    }
+
+    /// Applies a ternary operator to its operands.
+    /// Defaults to the built-in semantics, i.e. a clamp of `a` between `b` and `c`.
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        a: i32,
+        b: i32,
+        c: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        op.apply_ternary(a, b, c, span)
+    }
+}
+
+/// The built-in semantics, used by [`try_parse_with`](crate::try_parse_with):
+/// every operation is checked for overflow and division by zero,
+/// and divisions are rounded with the [`round_mode`](ParserConfig::round_mode),
+/// while a division by zero gives the [`div_by_zero`](ParserConfig::div_by_zero) result,
+/// unless the operator has one of the [`overrides`](ParserConfig::overrides).
+/// Every result is clamped to the [`operator_caps`](ParserConfig::operator_caps),
+/// and checked against the [`max_intermediate`](ParserConfig::max_intermediate) bound.
+impl OpSemantics for ParserConfig {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        let result = match self.overrides.get(&op) {
+            Some(f) => f.apply(a, b, span),
+            None if op == Operator::Div && b == 0 => self
+                .div_by_zero
+                .value()
+                .ok_or(ParseError::DivisionByZero(span)),
+            None => op.apply(a, b, self.round_mode, span),
+        };
+        self.bounded(self.capped(op, result?), span)
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        self.bounded(self.capped(op, op.apply_unary(a, span)?), span)
+    }
+
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        a: i32,
+        b: i32,
+        c: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        self.bounded(self.capped(op, op.apply_ternary(a, b, c, span)?), span)
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every operator is applied by the given semantics.
+///
+/// ```
+/// use arithmetic_parser::{parse_with_semantics, OpSemantics, Operator, ParseError, Span};
+///
+/// struct Saturating;
+///
+/// impl OpSemantics for Saturating {
+///     fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+///         match op {
+///             Operator::Sum => Ok(a.saturating_add(b)),
+///             Operator::Sub => Ok(a.saturating_sub(b)),
+///             Operator::Mul => Ok(a.saturating_mul(b)),
+///             _ => Err(ParseError::DisallowedOperator(op, span)),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(parse_with_semantics("2147483647a1", &Saturating), Ok(i32::MAX));
+/// ```
+pub fn parse_with_semantics(string: &str, semantics: &impl OpSemantics) -> Result<i32, ParseError> {
+    eval_spanned_rpn(
+        spanned_rpn(string, &ParserConfig::default(), None)?,
+        semantics,
+    )
+}
+
+/// An operation applied while evaluating an expression, see [`parse_audit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub op: Operator,
+    /// The operands, as many as the [arity](Operator::arity) of the operator.
+    pub operands: Vec<i32>,
+    pub result: i32,
+}
+
+/// The operation with the conventional symbol of the operator, like `3 + 2 = 5`,
+/// or `-(1) = -1` for a unary operator.
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (op, result) = (self.op.symbol(), self.result);
+        match self.operands[..] {
+            [a, b] => write!(f, "{a} {op} {b} = {result}"),
+            [a] => write!(f, "{op}({a}) = {result}"),
+            [a, b, c] => write!(f, "{op}({a}, {b}, {c}) = {result}"),
+            _ => unreachable!("{:?} has 1 to 3 operands", self.op),
+        }
+    }
+}
+
+/// The built-in semantics, logging every operation it applies.
+struct Steps {
+    config: ParserConfig,
+    log: RefCell<Vec<Step>>,
+}
+
+impl Steps {
+    fn log(&self, op: Operator, operands: Vec<i32>, result: i32) -> Result<i32, ParseError> {
+        let step = Step {
+            op,
+            operands,
+            result,
+        };
+        self.log.borrow_mut().push(step);
+        Ok(result)
+    }
+}
+
+impl OpSemantics for Steps {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        let result = self.config.apply(op, a, b, span)?;
+        self.log(op, vec![a, b], result)
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        let result = self.config.apply_unary(op, a, span)?;
+        self.log(op, vec![a], result)
+    }
+
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        a: i32,
+        b: i32,
+        c: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        let result = self.config.apply_ternary(op, a, b, c, span)?;
+        self.log(op, vec![a, b, c], result)
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but also returns every operation
+/// in the order it is applied, like `3 + 2 = 5`, so that the evaluation order can be followed:
+/// `3a2c4` is evaluated as `3 + 2 = 5` and then `5 * 4 = 20`.
+pub fn parse_steps(string: &str) -> Result<(i32, Vec<String>), ParseError> {
+    let (result, steps) = parse_audit(string)?;
+    Ok((result, steps.iter().map(Step::to_string).collect()))
+}
+
+/// Same as [`parse_steps`], but every operation is a [`Step`], with its operands and result,
+/// so `3a2c4` gives the steps of `+` with `[3, 2]` and `5`, and of `*` with `[5, 4]` and `20`.
+pub fn parse_audit(string: &str) -> Result<(i32, Vec<Step>), ParseError> {
+    let steps = Steps {
+        config: ParserConfig::default(),
+        log: RefCell::default(),
+    };
+    let result = eval_spanned_rpn(spanned_rpn(string, &steps.config, None)?, &steps)?;
+    Ok((result, steps.log.into_inner()))
+}
+
+/// The built-in semantics, remembering the remainder of the last operation applied,
+/// which is `0` unless it is a division.
+#[derive(Default)]
+struct Remainder {
+    remainder: Cell<i32>,
+}
+
+impl OpSemantics for Remainder {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        let result = op.apply(a, b, RoundMode::default(), span)?;
+        let remainder = match op {
+            // The quotient is truncated, so the remainder is smaller than the divisor.
+            Operator::Div => (i64::from(a) - i64::from(result) * i64::from(b)) as i32,
+            _ => 0,
+        };
+        self.remainder.set(remainder);
+        Ok(result)
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        self.remainder.set(0);
+        op.apply_unary(a, span)
+    }
+
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        a: i32,
+        b: i32,
+        c: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        self.remainder.set(0);
+        op.apply_ternary(a, b, c, span)
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but also returns the remainder
+/// of the final operation when it is a division, so `3a4d2` gives `(3, 1)`,
+/// while the remainder of any other expression is `0`.
+/// The remainder has the sign of the dividend, like Rust's `%` operator.
+pub fn parse_divmod(string: &str) -> Result<(i32, i32), ParseError> {
+    let remainder = Remainder::default();
+    let quotient = parse_with_semantics(string, &remainder)?;
+    Ok((quotient, remainder.remainder.get()))
+}
+
+/// The built-in semantics, where a division must have no remainder.
+struct Exact;
+
+impl OpSemantics for Exact {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        // The remainder of `i32::MIN / -1` overflows, and so does the division.
+        if op == Operator::Div && a.checked_rem(b).is_some_and(|remainder| remainder != 0) {
+            return Err(ParseError::InexactDivision {
+                dividend: a,
+                divisor: b,
+            });
+        }
+        op.apply(a, b, RoundMode::default(), span)
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every division must have no remainder,
+/// or it produces [`ParseError::InexactDivision`], so `6d2` is `3` while `7d2` fails.
+/// Percentages are rounded as usual.
+pub fn parse_exact(string: &str) -> Result<i32, ParseError> {
+    parse_with_semantics(string, &Exact)
+}
+
+/// The built-in semantics, except that results wrap around on overflow,
+/// remembering whether any of them did.
+#[derive(Default)]
+struct Wrapping {
+    wrapped: Cell<bool>,
+}
+
+impl Wrapping {
+    fn wrap(&self, n: i64) -> i32 {
+        let wrapped = n as i32;
+        if i64::from(wrapped) != n {
+            self.wrapped.set(true);
+        }
+        wrapped
+    }
+}
+
+impl OpSemantics for Wrapping {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        // Every result of two i32 operands fits in an i64, before wrapping.
+        let (a, b) = (i64::from(a), i64::from(b));
+        let result = match op {
+            Operator::Sum => Some(a + b),
+            Operator::Sub => Some(a - b),
+            Operator::Mul => Some(a * b),
+            Operator::Div if b == 0 => return Err(ParseError::DivisionByZero(span)),
+            Operator::Div => RoundMode::default().divide(a, b),
+            Operator::PercentOf => RoundMode::default().divide(a * b, 100),
+            Operator::Gcd => gcd(a.unsigned_abs(), b.unsigned_abs()).try_into().ok(),
+            Operator::Lcm => {
+                lcm(a.unsigned_abs(), b.unsigned_abs()).and_then(|n| n.try_into().ok())
+            }
+            Operator::RoundTo => return Err(ParseError::DisallowedOperator(op, span)),
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        result
+            .map(|n| self.wrap(n))
+            .ok_or(ParseError::Overflow(span))
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        match op {
+            Operator::Neg => Ok(self.wrap(-i64::from(a))),
+            Operator::Sqrt => op.apply_unary(a, span),
+            Operator::Signum => Ok(a.signum()),
+            Operator::DigitSum => Ok(self.wrap(digit_sum(a.unsigned_abs().into()) as i64)),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        }
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but the result of an operation that overflows
+/// wraps around instead of failing, and the flag tells whether any operation wrapped,
+/// so `2147483647a1` gives `(-2147483648, true)`.
+/// A division by zero still fails.
+pub fn parse_wrapping_flagged(string: &str) -> Result<(i32, bool), ParseError> {
+    let wrapping = Wrapping::default();
+    let result = parse_with_semantics(string, &wrapping)?;
+    Ok((result, wrapping.wrapped.get()))
+}
+
+/// The category of the result of an expression, see [`parse_outcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOutcome {
+    /// The value of an expression evaluated without errors.
+    Value(i32),
+    /// The value of an expression where some operation overflowed, wrapping around.
+    Overflowed(i32),
+    /// An expression with a division by zero.
+    DivByZero,
+    /// An expression that cannot be evaluated for any other reason,
+    /// like an invalid character or a missing operand.
+    SyntaxError(ParseError),
+}
+
+/// Same as [`try_parse`](crate::try_parse), but the result is flattened into its category,
+/// so that the common cases can be told apart with a single match:
+/// `2147483647a1` gives `Overflowed(-2147483648)`, and `1d0` gives `DivByZero`.
+pub fn parse_outcome(string: &str) -> ParseOutcome {
+    match parse_wrapping_flagged(string) {
+        Ok((n, false)) => ParseOutcome::Value(n),
+        Ok((n, true)) => ParseOutcome::Overflowed(n),
+        Err(ParseError::DivisionByZero(_)) => ParseOutcome::DivByZero,
+        Err(err) => ParseOutcome::SyntaxError(err),
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but returns `fallback` if parsing
+/// or evaluation fails for any reason, so `1a3d0` gives `fallback`.
+/// See [`parse_or_each`] to replace the failing operations only.
+pub fn parse_or(string: &str, fallback: i32) -> i32 {
+    crate::try_parse(string).unwrap_or(fallback)
+}
+
+/// The built-in semantics, where an operation that fails results in the fallback value.
+struct Fallback(i32);
+
+impl OpSemantics for Fallback {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        Ok(op.apply(a, b, RoundMode::default(), span).unwrap_or(self.0))
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        Ok(op.apply_unary(a, span).unwrap_or(self.0))
+    }
+
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        a: i32,
+        b: i32,
+        c: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        Ok(op.apply_ternary(a, b, c, span).unwrap_or(self.0))
+    }
+}
+
+/// Same as [`parse_or`], but only the operations whose evaluation fails,
+/// like a division by zero or an overflow, result in `fallback`,
+/// and the evaluation goes on with it: with a fallback of `0`, `1ae3d0f` gives `1`.
+/// An expression that cannot be parsed still gives `fallback` as a whole.
+pub fn parse_or_each(string: &str, fallback: i32) -> i32 {
+    parse_with_semantics(string, &Fallback(fallback)).unwrap_or(fallback)
+}
+
+/// The arithmetic of nim-values, where both the sum and the difference are the bitwise XOR,
+/// and the product is the nim-multiplication, so that the non negative numbers are a field.
+struct Nim;
+
+/// The nim-product of two numbers below `2^bits`, where `bits` is a power of two,
+/// which is below `2^bits` too.
+fn nim_mul(a: u64, b: u64, bits: u32) -> u64 {
+    if a < 2 || b < 2 {
+        return a * b;
+    }
+    // With `F = 2^half`, `F ⊗ F = F ⊕ F / 2`, so the product of
+    // `a1 ⊗ F ⊕ a0` and `b1 ⊗ F ⊕ b0` can be split in products of halves.
+    let half = bits / 2;
+    let mask = (1 << half) - 1;
+    let (a1, a0) = (a >> half, a & mask);
+    let (b1, b0) = (b >> half, b & mask);
+    let low = nim_mul(a0, b0, half);
+    let high = nim_mul(a1, b1, half);
+    let middle = nim_mul(a1 ^ a0, b1 ^ b0, half);
+    ((middle ^ low) << half) | (low ^ nim_mul(high, 1 << (half - 1), half))
+}
+
+impl OpSemantics for Nim {
+    fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+        // Literals are not negative, and neither are the results.
+        let (a, b) = (a as u64, b as u64);
+        let result = match op {
+            Operator::Sum | Operator::Sub => a ^ b,
+            Operator::Mul => nim_mul(a, b, 32),
+            _ => return Err(ParseError::DisallowedOperator(op, span)),
+        };
+        result.try_into().map_err(|_| ParseError::Overflow(span))
+    }
+
+    fn apply_unary(&self, op: Operator, a: i32, span: Span) -> Result<i32, ParseError> {
+        match op {
+            // Every number is its own opposite.
+            Operator::Neg => Ok(a),
+            _ => Err(ParseError::DisallowedOperator(op, span)),
+        }
+    }
+
+    fn apply_ternary(
+        &self,
+        op: Operator,
+        _: i32,
+        _: i32,
+        _: i32,
+        span: Span,
+    ) -> Result<i32, ParseError> {
+        Err(ParseError::DisallowedOperator(op, span))
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but with the arithmetic of nim-values
+/// of combinatorial game theory: `a` and `b` are the bitwise XOR, `c` is the nim-multiplication
+/// and `n` leaves its operand unchanged, so `5a3` is `6` and `2c2` is `3`.
+/// Any other operator produces [`ParseError::DisallowedOperator`],
+/// and a product that does not fit in an `i32` overflows.
+pub fn parse_nim(string: &str) -> Result<i32, ParseError> {
+    parse_with_semantics(string, &Nim)
+}
+
+#[test]
+fn parse_with_semantics_tests() {
+    struct Modular(i32);
+
+    impl OpSemantics for Modular {
+        fn apply(&self, op: Operator, a: i32, b: i32, span: Span) -> Result<i32, ParseError> {
+            let result = ParserConfig::default().apply(op, a, b, span)?;
+            Ok(result.rem_euclid(self.0))
+        }
+
+        fn apply_unary(&self, _: Operator, a: i32, _: Span) -> Result<i32, ParseError> {
+            Ok((-a).rem_euclid(self.0))
+        }
+    }
+
+    assert_eq!(parse_with_semantics("5a4c3", &Modular(7)), Ok(6));
+    assert_eq!(parse_with_semantics("n3", &Modular(7)), Ok(4));
+    assert_eq!(
+        parse_with_semantics("1d0", &Modular(7)),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    let config = ParserConfig::default();
+    assert_eq!(parse_with_semantics("3c4d2aee2a4c41fc4f", &config), Ok(990));
+}
+
+#[test]
+fn parse_audit_tests() {
+    let step = |op, operands: &[i32], result| Step {
+        op,
+        operands: operands.to_vec(),
+        result,
+    };
+    assert_eq!(
+        parse_audit("3a2c4"),
+        Ok((
+            20,
+            vec![
+                step(Operator::Sum, &[3, 2], 5),
+                step(Operator::Mul, &[5, 4], 20)
+            ]
+        ))
+    );
+    assert_eq!(
+        parse_audit("n15k0k10"),
+        Ok((
+            0,
+            vec![
+                step(Operator::Neg, &[15], -15),
+                step(Operator::Clamp, &[-15, 0, 10], 0)
+            ]
+        ))
+    );
+    assert_eq!(parse_audit("7"), Ok((7, vec![])));
+    assert_eq!(
+        parse_audit("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn parse_steps_tests() {
+    assert_eq!(
+        parse_steps("3a2c4"),
+        Ok((20, vec!["3 + 2 = 5".to_owned(), "5 * 4 = 20".to_owned()]))
+    );
+    assert_eq!(
+        parse_steps("3ae2c4fbn1"),
+        Ok((
+            12,
+            vec![
+                "2 * 4 = 8".to_owned(),
+                "3 + 8 = 11".to_owned(),
+                "-(1) = -1".to_owned(),
+                "11 - -1 = 12".to_owned(),
+            ]
+        ))
+    );
+    assert_eq!(parse_steps("7"), Ok((7, vec![])));
+    assert_eq!(
+        parse_steps("1a2d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 5)))
+    );
+}
+
+#[test]
+fn parse_wrapping_flagged_tests() {
+    assert_eq!(parse_wrapping_flagged("3a2c4"), Ok((20, false)));
+    assert_eq!(parse_wrapping_flagged("2147483647a1"), Ok((i32::MIN, true)));
+    assert_eq!(
+        parse_wrapping_flagged("2147483647a1b1"),
+        Ok((i32::MAX, true))
+    );
+    assert_eq!(parse_wrapping_flagged("65536c65536"), Ok((0, true)));
+    assert_eq!(
+        parse_wrapping_flagged("0b2147483647b1dn1"),
+        Ok((i32::MIN, true))
+    );
+    assert_eq!(
+        parse_wrapping_flagged("n2147483647b1h0"),
+        Ok((i32::MIN, true))
+    );
+    assert_eq!(
+        parse_wrapping_flagged("2147483647a1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 14)))
+    );
+}
+
+#[test]
+fn parse_or_tests() {
+    assert_eq!(parse_or("3a2c4", -1), 20);
+    assert_eq!(parse_or("1a3d0", -1), -1);
+    assert_eq!(parse_or("3ag", -1), -1);
+    assert_eq!(parse_or_each("3a2c4", -1), 20);
+    assert_eq!(parse_or_each("1ae3d0f", 0), 1);
+    assert_eq!(parse_or_each("1a3d0c5", 2), 10);
+    assert_eq!(parse_or_each("2147483647a1b7", 0), -7);
+    assert_eq!(parse_or_each("3ag", -1), -1);
+}
+
+#[test]
+fn parse_exact_tests() {
+    assert_eq!(parse_exact("6d2"), Ok(3));
+    assert_eq!(
+        parse_exact("7d2"),
+        Err(ParseError::InexactDivision {
+            dividend: 7,
+            divisor: 2
+        })
+    );
+    assert_eq!(
+        parse_exact("12d4a2d2"),
+        Err(ParseError::InexactDivision {
+            dividend: 5,
+            divisor: 2
+        })
+    );
+    assert_eq!(
+        parse_exact("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+    assert_eq!(
+        parse_exact("n2147483647b1dn1"),
+        Err(ParseError::Overflow(Span::new(0, 16)))
+    );
+}
+
+#[test]
+fn parse_divmod_tests() {
+    assert_eq!(parse_divmod("3a4d2"), Ok((3, 1)));
+    assert_eq!(parse_divmod("17d5"), Ok((3, 2)));
+    assert_eq!(parse_divmod("n17d5"), Ok((-3, -2)));
+    assert_eq!(parse_divmod("17dn5"), Ok((-3, 2)));
+    assert_eq!(parse_divmod("7d2a1"), Ok((4, 0)));
+    assert_eq!(parse_divmod("ne7d2f"), Ok((-3, 0)));
+    assert_eq!(parse_divmod("12d4"), Ok((3, 0)));
+    assert_eq!(parse_divmod(""), Ok((0, 0)));
+    assert_eq!(
+        parse_divmod("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
+
+#[test]
+fn parse_nim_tests() {
+    assert_eq!(parse_nim("5a3"), Ok(6));
+    assert_eq!(parse_nim("5b3"), Ok(6));
+    assert_eq!(parse_nim("n7a7"), Ok(0));
+    assert_eq!(parse_nim("2c2"), Ok(3));
+    assert_eq!(parse_nim("2c3"), Ok(1));
+    assert_eq!(parse_nim("4c4"), Ok(6));
+    assert_eq!(parse_nim("16c16"), Ok(24));
+    assert_eq!(parse_nim("7ce9a3a5f"), parse_nim("7c9ae7c3ae7c5ff"));
+    assert_eq!(
+        parse_nim("6d2"),
+        Err(ParseError::DisallowedOperator(
+            Operator::Div,
+            Span::new(0, 3)
+        ))
+    );
+    assert_eq!(
+        parse_nim("65536c65536c65536"),
+        Err(ParseError::Overflow(Span::new(0, 17)))
+    );
+}
+
+#[test]
+fn parse_outcome_tests() {
+    assert_eq!(parse_outcome("3a2c4"), ParseOutcome::Value(20));
+    assert_eq!(
+        parse_outcome("2147483647a1"),
+        ParseOutcome::Overflowed(i32::MIN)
+    );
+    assert_eq!(parse_outcome("1ae3d0f"), ParseOutcome::DivByZero);
+    assert_eq!(
+        parse_outcome("3a"),
+        ParseOutcome::SyntaxError(ParseError::EmptyOperand(Span::new(1, 2)))
+    );
+}
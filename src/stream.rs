@@ -0,0 +1,124 @@
+use std::{future::poll_fn, pin::pin};
+
+use futures_core::Stream;
+
+use crate::{
+    eval_spanned_rpn, shunting_yard, token::is_identifier_char, token::Tokens, ParseError,
+    ParserConfig, Span, Token,
+};
+
+/// Same as [`try_parse`](crate::try_parse), but the characters of the expression
+/// are read from an asynchronous stream, as they become available.
+///
+/// Only the characters of the number literal being read are buffered,
+/// the expression is converted to tokens as it is read, and evaluated at the end of the stream.
+pub async fn parse_async<S: Stream<Item = char>>(stream: S) -> Result<i32, ParseError> {
+    let mut stream = pin!(stream);
+    let mut tokens = Vec::new();
+    // The literal or the constant being read, with its position.
+    let mut word = String::new();
+    let mut start = 0;
+    let mut position = 0;
+
+    while let Some(c) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        let continues = match word.chars().next() {
+            Some(first) if first.is_ascii_digit() => c.is_ascii_digit(),
+            Some(_) => is_identifier_char(c),
+            None => false,
+        };
+        if !continues {
+            lex(&word, start, &mut tokens)?;
+            word.clear();
+            start = position;
+        }
+        word.push(c);
+        position += c.len_utf8();
+        if !(c.is_ascii_digit() || c.is_ascii_uppercase()) {
+            lex(&word, start, &mut tokens)?;
+            word.clear();
+            start = position;
+        }
+    }
+    lex(&word, start, &mut tokens)?;
+
+    let config = ParserConfig::default();
+    eval_spanned_rpn(
+        shunting_yard(tokens.into_iter().map(Ok), &config, None)?,
+        &config,
+    )
+}
+
+/// Appends the tokens of a part of the expression starting at `start`.
+fn lex(part: &str, start: usize, tokens: &mut Vec<(Token, Span)>) -> Result<(), ParseError> {
+    for token in Tokens::new(part) {
+        let (token, span) = token.map_err(|err| err.shifted(start))?;
+        tokens.push((token, Span::new(span.start + start, span.end + start)));
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_async_tests() {
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    // A stream that is pending before every character.
+    struct Chars<I> {
+        chars: I,
+        ready: bool,
+    }
+
+    impl<I: Iterator<Item = char> + Unpin> Stream for Chars<I> {
+        type Item = char;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<char>> {
+            self.ready = !self.ready;
+            if self.ready {
+                Poll::Ready(self.chars.next())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    let parse = |string: &str| {
+        let mut future = pin!(parse_async(Chars {
+            chars: string.chars(),
+            ready: false,
+        }));
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+                return result;
+            }
+        }
+    };
+
+    assert_eq!(parse("3a2c4"), Ok(20));
+    assert_eq!(parse("500a10b66c32"), Ok(14208));
+    assert_eq!(parse("3c4d2aee2a4c41fc4f"), Ok(990));
+    assert_eq!(parse(""), Ok(0));
+    assert_eq!(
+        parse("12a3g"),
+        Err(ParseError::InvalidCharacter('g', Span::new(4, 5)))
+    );
+    assert_eq!(
+        parse("1a2147483648"),
+        Err(ParseError::NumberOverflow(Span::new(2, 12)))
+    );
+    assert_eq!(
+        parse("1aPI"),
+        Err(ParseError::UnknownConstant(
+            "PI".to_owned(),
+            Span::new(2, 4)
+        ))
+    );
+    assert_eq!(
+        parse("1d0"),
+        Err(ParseError::DivisionByZero(Span::new(0, 3)))
+    );
+}
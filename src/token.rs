@@ -0,0 +1,665 @@
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    ops::{ControlFlow, Range},
+};
+
+use crate::{NumberFormat, Operator, ParseError, ParserConfig, Span};
+
+/// A token of an expression, whose number literals are of type `N`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token<N = i32> {
+    Number(N),
+    Operator(Operator),
+    /// `$`, the result of the previous evaluation of a [`Repl`](crate::Repl).
+    Previous,
+}
+
+/// A type of number literal that can be read by the lexer.
+pub(crate) trait Literal: Copy {
+    /// The length in bytes of the literal at the start of `rest`, which starts with a digit.
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize;
+
+    /// Parses a literal, returns `None` if it is out of range.
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self>;
+
+    /// Converts the value of a named constant, returns `None` if it is out of range.
+    fn from_constant(value: i64) -> Option<Self>;
+
+    /// The literal standing for a name which is not a constant,
+    /// returns `None` if the type has no variables, making the name unknown.
+    fn variable() -> Option<Self> {
+        None
+    }
+
+    /// The value of an integer literal, checked against [`ParserConfig::max_literal`],
+    /// returns `None` for other literals, which are not checked.
+    fn integer_value(self) -> Option<i64> {
+        None
+    }
+}
+
+/// The length in bytes of the run of digits at the start of `rest`.
+pub(crate) fn digits_len(rest: &str) -> usize {
+    rest.find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len())
+}
+
+impl Literal for i32 {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        format.integer_len(rest)
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (digits, radix) = format.radix(literal);
+        Self::from_str_radix(&digits, radix).ok()
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        value.try_into().ok()
+    }
+
+    fn integer_value(self) -> Option<i64> {
+        Some(self.into())
+    }
+}
+
+impl Literal for i64 {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        format.integer_len(rest)
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (digits, radix) = format.radix(literal);
+        Self::from_str_radix(&digits, radix).ok()
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        Some(value)
+    }
+
+    fn integer_value(self) -> Option<i64> {
+        Some(self)
+    }
+}
+
+/// Iterator over the tokens of an expression, paired with their spans.
+/// A number literal is a single token, and so is a constant, which is replaced by its value.
+/// Iteration stops after the first error.
+pub(crate) struct Tokens<'a, N = i32> {
+    string: &'a str,
+    position: usize,
+    format: NumberFormat,
+    constants: Option<&'a HashMap<String, i64>>,
+    /// Whether symbols, words and whitespace are accepted, see [`ParserConfig::flexible_operators`].
+    flexible: bool,
+    max_literal: Option<i32>,
+    literal: PhantomData<N>,
+}
+
+impl<'a, N: Literal> Tokens<'a, N> {
+    pub(crate) fn new(string: &'a str) -> Self {
+        Self {
+            string,
+            position: 0,
+            format: NumberFormat::default(),
+            constants: None,
+            flexible: false,
+            max_literal: None,
+            literal: PhantomData,
+        }
+    }
+
+    /// Tokens with the number format and the constants of the given configuration.
+    pub(crate) fn with_config(string: &'a str, config: &'a ParserConfig) -> Self {
+        Self {
+            format: config.number_format,
+            constants: Some(&config.constants),
+            flexible: config.flexible_operators,
+            max_literal: config.max_literal,
+            ..Self::new(string)
+        }
+    }
+
+    fn lex(&mut self, c: char) -> Result<(Token<N>, Span), ParseError> {
+        let start = self.position;
+        let rest = &self.string[start..];
+        if c.is_ascii_digit() {
+            self.position += N::literal_len(rest, &self.format);
+            let span = Span::new(start, self.position);
+            let number = N::parse_literal(&self.string[start..self.position], &self.format)
+                .ok_or(ParseError::NumberOverflow(span))?;
+            if let (Some(max), Some(value)) = (self.max_literal, number.integer_value()) {
+                if value > max.into() {
+                    return Err(ParseError::LiteralTooLarge { value, span });
+                }
+            }
+            return Ok((Token::Number(number), span));
+        }
+        if c.is_ascii_uppercase() {
+            self.position += rest
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(rest.len());
+            let span = Span::new(start, self.position);
+            let name = &self.string[start..self.position];
+            let number = match self.constants.and_then(|constants| constants.get(name)) {
+                Some(value) => N::from_constant(*value).ok_or(ParseError::NumberOverflow(span))?,
+                None => N::variable()
+                    .ok_or_else(|| ParseError::UnknownConstant(name.to_owned(), span))?,
+            };
+            return Ok((Token::Number(number), span));
+        }
+
+        if self.flexible {
+            if let Some((o, len)) = flexible_operator(rest, start)? {
+                self.position += len;
+                return Ok((Token::Operator(o), Span::new(start, self.position)));
+            }
+        }
+
+        self.position += c.len_utf8();
+        let span = Span::new(start, self.position);
+        let token = match c {
+            '$' => Token::Previous,
+            _ => match Operator::from_char(c) {
+                Some(o) => Token::Operator(o),
+                None => return Err(ParseError::InvalidCharacter(c, span)),
+            },
+        };
+        Ok((token, span))
+    }
+}
+
+impl<N: Literal> Iterator for Tokens<'_, N> {
+    type Item = Result<(Token<N>, Span), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.flexible {
+            let rest = &self.string[self.position..];
+            self.position += rest.len() - rest.trim_start().len();
+        }
+        let c = self.string[self.position..].chars().next()?;
+        let token = self.lex(c);
+        if token.is_err() {
+            self.position = self.string.len();
+        }
+        Some(token)
+    }
+}
+
+/// The words of the operators, see [`ParserConfig::flexible_operators`].
+const OPERATOR_WORDS: [(&str, Operator); 4] = [
+    ("plus", Operator::Sum),
+    ("minus", Operator::Sub),
+    ("times", Operator::Mul),
+    ("over", Operator::Div),
+];
+
+/// The symbol or the word of an operator at the start of `rest`, with its length in bytes.
+fn flexible_operator(rest: &str, start: usize) -> Result<Option<(Operator, usize)>, ParseError> {
+    let symbol = match rest.as_bytes()[0] {
+        b'+' => Some(Operator::Sum),
+        b'-' => Some(Operator::Sub),
+        b'*' => Some(Operator::Mul),
+        b'/' => Some(Operator::Div),
+        b'(' => Some(Operator::LBra),
+        b')' => Some(Operator::RBra),
+        _ => None,
+    };
+    if let Some(o) = symbol {
+        return Ok(Some((o, 1)));
+    }
+    for (word, o) in OPERATOR_WORDS {
+        if let Some(after) = rest.strip_prefix(word) {
+            let letters = after
+                .find(|c: char| !c.is_ascii_lowercase())
+                .unwrap_or(after.len());
+            if letters > 0 {
+                let span = Span::new(start, start + word.len() + letters);
+                return Err(ParseError::AmbiguousWord(span));
+            }
+            return Ok(Some((o, word.len())));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether `c` can be part of the name of a constant, which starts with an uppercase letter.
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'
+}
+
+/// Splits an expression into its tokens.
+/// A run of digits is a single number token, so `12a3` has 3 tokens.
+pub fn tokenize(string: &str) -> Result<Vec<Token>, ParseError> {
+    Tokens::new(string)
+        .map(|token| token.map(|(token, _)| token))
+        .collect()
+}
+
+/// Calls `f` with every token of an expression and its byte range, without storing them,
+/// until `f` returns [`ControlFlow::Break`]. Fails on the first invalid token
+/// that is reached, so the tokens after a break are not checked.
+pub fn tokenize_callback(
+    string: &str,
+    mut f: impl FnMut(Token, Range<usize>) -> ControlFlow<()>,
+) -> Result<(), ParseError> {
+    for token in Tokens::new(string) {
+        let (token, span) = token?;
+        if f(token, span.start..span.end).is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every number literal of an expression fits in `bits` bits,
+/// as an unsigned number, or as a signed one if `signed` is set,
+/// where a literal right after an odd number of `n` is negative,
+/// so `n128` fits in 8 signed bits while `128` and `nn128` do not.
+/// The expression is not evaluated, so its result may still be wider.
+pub fn validate_widths(string: &str, bits: u32, signed: bool) -> Result<(), ParseError> {
+    let mut negated = false;
+    for token in Tokens::<i64>::new(string) {
+        let (token, span) = token?;
+        if let Token::Number(value) = token {
+            // The most negative value has the magnitude `2^(bits - 1)`, one more than the largest.
+            let max = match signed {
+                false => (1_i128 << bits.min(64)) - 1,
+                true => (1_i128 << bits.saturating_sub(1).min(64)) - i128::from(!negated),
+            };
+            if i128::from(value) > max {
+                return Err(ParseError::LiteralTooWide { value, span });
+            }
+        }
+        // Consecutive negations cancel out in pairs.
+        negated = token == Token::Operator(Operator::Neg) && !negated;
+    }
+    Ok(())
+}
+
+/// Counts the tokens of an expression in a single pass, without storing them,
+/// e.g. `3a22c4` has 5 tokens. Fails on the first invalid token.
+pub fn token_count(string: &str) -> Result<usize, ParseError> {
+    Tokens::<i32>::new(string).try_fold(0, |count, token| token.map(|_| count + 1))
+}
+
+/// Counts how many times each operator appears in an expression, in a single pass,
+/// e.g. `3a2a4c1` has two [`Operator::Sum`] and one [`Operator::Mul`].
+/// Parentheses are counted as operators too. Fails on the first invalid token.
+pub fn operator_histogram(string: &str) -> Result<HashMap<Operator, usize>, ParseError> {
+    Tokens::<i32>::new(string).try_fold(HashMap::new(), |mut histogram, token| {
+        if let (Token::Operator(o), _) = token? {
+            *histogram.entry(o).or_insert(0) += 1;
+        }
+        Ok(histogram)
+    })
+}
+
+/// Counts the distinct values of the number literals of an expression, in a single pass,
+/// e.g. `12a3c12` has 2, and so has `7a007c3`, since `007` is `7`.
+/// Fails on the first invalid token.
+pub fn distinct_operand_count(string: &str) -> Result<usize, ParseError> {
+    let mut values = HashSet::new();
+    for token in Tokens::<i32>::new(string) {
+        if let (Token::Number(n), _) = token? {
+            values.insert(n);
+        }
+    }
+    Ok(values.len())
+}
+
+/// Splits an expression into its tokens, like [`tokenize`], ignoring whitespace,
+/// then writes them back with the rule characters of the operators,
+/// so that `3 a 2 c 4` is `3a2c4`. The result equals the input without whitespace.
+pub fn reserialize(string: &str) -> Result<String, ParseError> {
+    let mut result = String::with_capacity(string.len());
+    let mut offset = 0;
+    for part in string.split(char::is_whitespace) {
+        for token in Tokens::<i32>::new(part) {
+            // Literals are written as in the source, so that `007` stays `007`.
+            match token.map_err(|err| err.shifted(offset))? {
+                (Token::Number(_), span) => result.push_str(&part[span.start..span.end]),
+                (Token::Operator(o), _) => result.push(o.to_char()),
+                (Token::Previous, _) => result.push('$'),
+            }
+        }
+        // The separator is a single whitespace character.
+        offset += part.len()
+            + string[offset + part.len()..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8);
+    }
+    Ok(result)
+}
+
+/// A difference between the tokens of two versions of an expression, see [`token_diff`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenChange {
+    /// A token of the new version only.
+    Inserted(Token),
+    /// A token of the old version only.
+    Removed(Token),
+    /// A token of the old version replaced by one of the new version, like `3` by `30`.
+    Changed(Token, Token),
+}
+
+/// The tokens inserted, removed and changed from the `old` version of an expression
+/// to the `new` one, in order, using the longest common subsequence of their tokens.
+/// A number literal is a single token, so `3a2` to `30a2` is a single change.
+pub fn token_diff(old: &str, new: &str) -> Result<Vec<TokenChange>, ParseError> {
+    let (old, new) = (tokenize(old)?, tokenize(new)?);
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    // Removals not yet paired with an insertion, which would make them changes.
+    let mut removed = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.extend(removed.drain(..).map(TokenChange::Removed));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            removed.push(old[i]);
+            i += 1;
+        } else {
+            changes.push(if removed.is_empty() {
+                TokenChange::Inserted(new[j])
+            } else {
+                TokenChange::Changed(removed.remove(0), new[j])
+            });
+            j += 1;
+        }
+    }
+    changes.extend(removed.into_iter().map(TokenChange::Removed));
+    Ok(changes)
+}
+
+#[test]
+fn tokens_tests() {
+    let tokens = Tokens::<i32>::new("12ae3$").collect::<Result<Vec<_>, _>>();
+    assert_eq!(
+        tokens,
+        Ok(vec![
+            (Token::Number(12), Span::new(0, 2)),
+            (Token::Operator(Operator::Sum), Span::new(2, 3)),
+            (Token::Operator(Operator::LBra), Span::new(3, 4)),
+            (Token::Number(3), Span::new(4, 5)),
+            (Token::Previous, Span::new(5, 6)),
+        ])
+    );
+    let mut tokens = Tokens::<i32>::new("1g2");
+    assert!(tokens.next().unwrap().is_ok());
+    assert_eq!(
+        tokens.next(),
+        Some(Err(ParseError::InvalidCharacter('g', Span::new(1, 2))))
+    );
+    assert_eq!(tokens.next(), None);
+}
+
+#[test]
+fn token_count_tests() {
+    assert_eq!(token_count(""), Ok(0));
+    assert_eq!(token_count("3a22c4"), Ok(5));
+    assert_eq!(token_count("3c4d2aee2a4c41fc4f"), Ok(17));
+    assert_eq!(
+        token_count("3ag"),
+        Err(ParseError::InvalidCharacter('g', Span::new(2, 3)))
+    );
+    assert_eq!(
+        token_count("99999999999"),
+        Err(ParseError::NumberOverflow(Span::new(0, 11)))
+    );
+}
+
+#[test]
+fn distinct_operand_count_tests() {
+    assert_eq!(distinct_operand_count(""), Ok(0));
+    assert_eq!(distinct_operand_count("12a3c12"), Ok(2));
+    assert_eq!(distinct_operand_count("7a007c3"), Ok(2));
+    assert_eq!(distinct_operand_count("e1a2fc3d4b5"), Ok(5));
+    assert_eq!(
+        distinct_operand_count("3ag"),
+        Err(ParseError::InvalidCharacter('g', Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn tokenize_tests() {
+    assert_eq!(
+        tokenize("12an3"),
+        Ok(vec![
+            Token::Number(12),
+            Token::Operator(Operator::Sum),
+            Token::Operator(Operator::Neg),
+            Token::Number(3),
+        ])
+    );
+    assert_eq!(tokenize(""), Ok(vec![]));
+    assert_eq!(
+        tokenize("1x"),
+        Err(ParseError::InvalidCharacter('x', Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn operator_histogram_tests() {
+    let histogram = operator_histogram("3a2a4c1ae5f").unwrap();
+    assert_eq!(
+        histogram,
+        HashMap::from([
+            (Operator::Sum, 3),
+            (Operator::Mul, 1),
+            (Operator::LBra, 1),
+            (Operator::RBra, 1),
+        ])
+    );
+    assert_eq!(operator_histogram("42"), Ok(HashMap::new()));
+    assert_eq!(
+        operator_histogram("3ag"),
+        Err(ParseError::InvalidCharacter('g', Span::new(2, 3)))
+    );
+}
+
+#[test]
+fn token_diff_tests() {
+    use TokenChange::{Changed, Inserted, Removed};
+
+    assert_eq!(token_diff("3a2", "3a2"), Ok(vec![]));
+    assert_eq!(
+        token_diff("3a2", "30a2"),
+        Ok(vec![Changed(Token::Number(3), Token::Number(30))])
+    );
+    assert_eq!(
+        token_diff("3a2", "3a2c4"),
+        Ok(vec![
+            Inserted(Token::Operator(Operator::Mul)),
+            Inserted(Token::Number(4)),
+        ])
+    );
+    assert_eq!(
+        token_diff("1ae3c4f", "1a3"),
+        Ok(vec![
+            Removed(Token::Operator(Operator::LBra)),
+            Removed(Token::Operator(Operator::Mul)),
+            Removed(Token::Number(4)),
+            Removed(Token::Operator(Operator::RBra)),
+        ])
+    );
+    assert_eq!(
+        token_diff("1a2b3", "1c2d3"),
+        Ok(vec![
+            Changed(
+                Token::Operator(Operator::Sum),
+                Token::Operator(Operator::Mul)
+            ),
+            Changed(
+                Token::Operator(Operator::Sub),
+                Token::Operator(Operator::Div)
+            ),
+        ])
+    );
+    assert_eq!(
+        token_diff("1a2", "1g"),
+        Err(ParseError::InvalidCharacter('g', Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn reserialize_tests() {
+    assert_eq!(reserialize("3 a 2 c 4"), Ok("3a2c4".to_owned()));
+    assert_eq!(
+        reserialize("\t3c4d2aee2a4c41fc4f\n"),
+        Ok("3c4d2aee2a4c41fc4f".to_owned())
+    );
+    for c in Operator::ALL.map(Operator::to_char) {
+        assert_eq!(Operator::from_char(c).map(Operator::to_char), Some(c));
+    }
+    let all = Operator::ALL
+        .map(Operator::to_char)
+        .iter()
+        .collect::<String>();
+    assert_eq!(reserialize(&all), Ok(all));
+    assert_eq!(reserialize("$ n 1"), Ok("$n1".to_owned()));
+    assert_eq!(reserialize("007a 1"), Ok("007a1".to_owned()));
+    assert_eq!(
+        reserialize("3 a\u{3000}2 g"),
+        Err(ParseError::InvalidCharacter('g', Span::new(8, 9)))
+    );
+}
+
+#[test]
+fn flexible_operators_tests() {
+    let config = ParserConfig {
+        flexible_operators: true,
+        ..Default::default()
+    };
+    let parse = |string| crate::try_parse_with(string, &config);
+    assert_eq!(parse("3 + 2 times 4"), Ok(20));
+    assert_eq!(parse("3 a 2 c 4"), Ok(20));
+    assert_eq!(parse("3+2*4"), Ok(20));
+    assert_eq!(parse("10 minus 4 over 3 b 1"), Ok(1));
+    assert_eq!(parse("3 + (2 times 4)"), Ok(11));
+    assert_eq!(parse("3ae2c4f"), Ok(11));
+    assert_eq!(parse("15p200"), Ok(30));
+    assert_eq!(parse(" n2 plus 3 "), Ok(1));
+    assert_eq!(
+        parse("3 plusn2"),
+        Err(ParseError::AmbiguousWord(Span::new(2, 7)))
+    );
+    assert_eq!(
+        parse("3 % 2"),
+        Err(ParseError::InvalidCharacter('%', Span::new(2, 3)))
+    );
+    assert_eq!(
+        crate::try_parse("3 + 2"),
+        Err(ParseError::InvalidCharacter(' ', Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn base_suffixes_tests() {
+    let config = ParserConfig {
+        number_format: NumberFormat {
+            base_suffixes: true,
+            ..NumberFormat::PLAIN
+        },
+        constants: std::collections::HashMap::from([("BIT".to_owned(), 5)]),
+        ..Default::default()
+    };
+    let parse = |string| crate::try_parse_with(string, &config);
+    assert_eq!(parse("1010B"), Ok(10));
+    assert_eq!(parse("777O"), Ok(511));
+    assert_eq!(parse("0FFHa1"), Ok(256));
+    assert_eq!(parse("1BHc2"), Ok(54));
+    assert_eq!(parse("10bn1B"), Ok(11));
+    assert_eq!(parse("12"), Ok(12));
+    assert_eq!(parse("7FFFFFFFH"), Ok(i32::MAX));
+    assert_eq!(
+        parse("80000000H"),
+        Err(ParseError::NumberOverflow(Span::new(0, 9)))
+    );
+    assert_eq!(parse("1BIT"), Err(ParseError::Malformed(Span::new(1, 4))));
+    assert_eq!(
+        parse("12B"),
+        Err(ParseError::UnknownConstant("B".to_owned(), Span::new(2, 3)))
+    );
+    assert_eq!(
+        crate::try_parse("1010B"),
+        Err(ParseError::UnknownConstant("B".to_owned(), Span::new(4, 5)))
+    );
+}
+
+#[test]
+fn tokenize_callback_tests() {
+    let mut tokens = Vec::new();
+    let result = tokenize_callback("12a3c", |token, range| {
+        tokens.push((token, range));
+        ControlFlow::Continue(())
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        tokens,
+        [
+            (Token::Number(12), 0..2),
+            (Token::Operator(Operator::Sum), 2..3),
+            (Token::Number(3), 3..4),
+            (Token::Operator(Operator::Mul), 4..5),
+        ]
+    );
+
+    // Stops at the first operator, before the invalid character.
+    let mut first = None;
+    let result = tokenize_callback("1a2g", |token, _| match token {
+        Token::Operator(o) => {
+            first = Some(o);
+            ControlFlow::Break(())
+        }
+        _ => ControlFlow::Continue(()),
+    });
+    assert_eq!((result, first), (Ok(()), Some(Operator::Sum)));
+    assert_eq!(
+        tokenize_callback("1g", |_, _| ControlFlow::Continue(())),
+        Err(ParseError::InvalidCharacter('g', Span::new(1, 2)))
+    );
+}
+
+#[test]
+fn validate_widths_tests() {
+    let too_wide = |value, start, end| {
+        Err(ParseError::LiteralTooWide {
+            value,
+            span: Span::new(start, end),
+        })
+    };
+    assert_eq!(validate_widths("255a1", 8, false), Ok(()));
+    assert_eq!(validate_widths("1a256", 8, false), too_wide(256, 2, 5));
+    assert_eq!(validate_widths("127bn128", 8, true), Ok(()));
+    assert_eq!(validate_widths("128", 8, true), too_wide(128, 0, 3));
+    assert_eq!(validate_widths("n129", 8, true), too_wide(129, 1, 4));
+    assert_eq!(validate_widths("nn128", 8, true), too_wide(128, 2, 5));
+    assert_eq!(validate_widths("nnn128", 8, true), Ok(()));
+    assert_eq!(validate_widths("5000000000", 64, true), Ok(()));
+    assert_eq!(validate_widths("1", 0, false), too_wide(1, 0, 1));
+    assert_eq!(
+        validate_widths("00000000256", 8, false),
+        too_wide(256, 0, 11)
+    );
+    assert_eq!(validate_widths("0", 0, false), Ok(()));
+    assert_eq!(
+        validate_widths("1a2g", 8, false),
+        Err(ParseError::InvalidCharacter('g', Span::new(3, 4)))
+    );
+}
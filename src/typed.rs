@@ -0,0 +1,180 @@
+use crate::{
+    digit_sum, gcd, lcm, spanned_rpn, token::Literal, NumberFormat, NumberOrOperator, Operator,
+    ParseError, ParserConfig, RoundMode, Span,
+};
+
+/// The widths in bits a literal can be annotated with.
+const WIDTHS: [u32; 4] = [8, 16, 32, 64];
+
+/// A signed integer with its width in bits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Typed {
+    value: i64,
+    bits: u32,
+}
+
+impl Typed {
+    /// The integer, if it fits in the given width.
+    fn new(value: i64, bits: u32) -> Option<Typed> {
+        let max = i64::MAX >> (64 - bits);
+        (-max - 1..=max)
+            .contains(&value)
+            .then_some(Typed { value, bits })
+    }
+
+    /// The result of an operation in the given width, which overflows if it does not fit.
+    fn result(value: Option<i64>, bits: u32, span: Span) -> Result<Typed, ParseError> {
+        value
+            .and_then(|value| Typed::new(value, bits))
+            .ok_or(ParseError::Overflow(span))
+    }
+
+    fn apply(op: Operator, n1: Typed, n2: Typed, span: Span) -> Result<Typed, ParseError> {
+        let (a, b) = (n1.value, n2.value);
+        let result = match op {
+            Operator::Sum => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div if b == 0 => return Err(ParseError::DivisionByZero(span)),
+            Operator::Div => RoundMode::default().divide(a, b),
+            Operator::PercentOf => a
+                .checked_mul(b)
+                .and_then(|n| RoundMode::default().divide(n, 100)),
+            Operator::Gcd => gcd(a.unsigned_abs(), b.unsigned_abs()).try_into().ok(),
+            Operator::Lcm => {
+                lcm(a.unsigned_abs(), b.unsigned_abs()).and_then(|n| n.try_into().ok())
+            }
+            Operator::RoundTo => return Err(ParseError::DisallowedOperator(op, span)),
+            _ => unreachable!("{op:?} is not a binary operator"),
+        };
+        Typed::result(result, n1.bits.max(n2.bits), span)
+    }
+
+    fn apply_unary(op: Operator, n: Typed, span: Span) -> Result<Typed, ParseError> {
+        let result = match op {
+            Operator::Neg => n.value.checked_neg(),
+            Operator::Signum => Some(n.value.signum()),
+            Operator::DigitSum => digit_sum(n.value.unsigned_abs()).try_into().ok(),
+            Operator::Sqrt if n.value < 0 => return Err(ParseError::NegativeSquareRoot(span)),
+            Operator::Sqrt => Some(n.value.isqrt()),
+            _ => unreachable!("{op:?} is not a unary operator"),
+        };
+        Typed::result(result, n.bits, span)
+    }
+
+    fn apply_ternary(
+        op: Operator,
+        n1: Typed,
+        n2: Typed,
+        n3: Typed,
+        span: Span,
+    ) -> Result<Typed, ParseError> {
+        match op {
+            Operator::Clamp if n2.value > n3.value => Err(ParseError::InvalidBounds(span)),
+            Operator::Clamp => {
+                let bits = n1.bits.max(n2.bits).max(n3.bits);
+                Typed::result(Some(n1.value.clamp(n2.value, n3.value)), bits, span)
+            }
+            _ => unreachable!("{op:?} is not a ternary operator"),
+        }
+    }
+}
+
+/// A number, optionally followed by `i` and its width, like `3i8`, or `i32` by default.
+impl Literal for Typed {
+    fn literal_len(rest: &str, format: &NumberFormat) -> usize {
+        let len = format.integer_len(rest);
+        let suffix = rest[len..].strip_prefix('i').map(|bits| {
+            let digits = bits
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(bits.len());
+            &bits[..digits]
+        });
+        match suffix {
+            Some(bits) if WIDTHS.iter().any(|width| width.to_string() == bits) => {
+                len + 1 + bits.len()
+            }
+            _ => len,
+        }
+    }
+
+    fn parse_literal(literal: &str, format: &NumberFormat) -> Option<Self> {
+        let (digits, bits) = match literal.split_once('i') {
+            Some((digits, bits)) => (digits, bits.parse().ok()?),
+            None => (literal, 32),
+        };
+        Typed::new(i64::parse_literal(digits, format)?, bits)
+    }
+
+    fn from_constant(value: i64) -> Option<Self> {
+        Typed::new(value, 32).or(Typed::new(value, 64))
+    }
+}
+
+/// Same as [`try_parse`](crate::try_parse), but every number literal has a width in bits,
+/// `8`, `16`, `32` or `64`, given by a suffix like in `100i8`, or `32` without it.
+/// Every operation is performed in the widest type of its operands, and overflows
+/// if its result does not fit in it: `100i8a27i8` is `127i8`, while `100i8a28i8` overflows,
+/// and `100i8a28` is `128i32`. Returns the value of the expression and its width.
+/// A literal that does not fit in its width produces [`ParseError::NumberOverflow`].
+pub fn parse_typed(string: &str) -> Result<(i64, u32), ParseError> {
+    let mut output_stack = Vec::<(Typed, Span)>::new();
+    for (token, span) in spanned_rpn(string, &ParserConfig::default(), None)? {
+        match token {
+            NumberOrOperator::Number(n) => output_stack.push((n, span)),
+            NumberOrOperator::Operator(o) if o.arity() == 1 => {
+                let (n, n_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = span.to(n_span);
+                output_stack.push((Typed::apply_unary(o, n, span)?, span));
+            }
+            NumberOrOperator::Operator(o) if o.arity() == 3 => {
+                let (n3, n3_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n2, _) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n3_span);
+                output_stack.push((Typed::apply_ternary(o, n1, n2, n3, span)?, span));
+            }
+            NumberOrOperator::Operator(o) => {
+                let (n2, n2_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let (n1, n1_span) = output_stack.pop().ok_or(ParseError::EmptyOperand(span))?;
+                let span = n1_span.to(n2_span);
+                output_stack.push((Typed::apply(o, n1, n2, span)?, span));
+            }
+        }
+    }
+
+    let result = output_stack
+        .pop()
+        .map_or(Typed { value: 0, bits: 32 }, |(n, _)| n);
+    Ok((result.value, result.bits))
+}
+
+#[test]
+fn parse_typed_tests() {
+    assert_eq!(parse_typed("100i8a27i8"), Ok((127, 8)));
+    assert_eq!(
+        parse_typed("100i8a28i8"),
+        Err(ParseError::Overflow(Span::new(0, 10)))
+    );
+    assert_eq!(parse_typed("100i8a28"), Ok((128, 32)));
+    assert_eq!(parse_typed("3a2c4"), Ok((20, 32)));
+    assert_eq!(
+        parse_typed("2147483647a1"),
+        Err(ParseError::Overflow(Span::new(0, 12)))
+    );
+    assert_eq!(parse_typed("2147483647a1i64"), Ok((2147483648, 64)));
+    assert_eq!(
+        parse_typed("n128i8"),
+        Err(ParseError::NumberOverflow(Span::new(1, 6)))
+    );
+    assert_eq!(parse_typed("n127i8b1i8"), Ok((-128, 8)));
+    assert_eq!(
+        parse_typed("n127i8bi8"),
+        Err(ParseError::InvalidCharacter('i', Span::new(7, 8)))
+    );
+    assert_eq!(
+        parse_typed("3i7"),
+        Err(ParseError::InvalidCharacter('i', Span::new(1, 2)))
+    );
+    assert_eq!(parse_typed(""), Ok((0, 32)));
+}